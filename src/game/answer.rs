@@ -1,21 +1,31 @@
+// Bevy 0.7's Bundle derive forgets each field after moving it, which clippy flags
+#![allow(clippy::forget_non_drop)]
+
 use bevy::prelude::*;
 use bevy::text::Text2dBounds;
 
 use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::setup::{GameMode, GameSettings};
 use crate::game::ui::{ScoreCount, QuestionCount};
 use crate::game::token::{Token, On, SideLength};
 use crate::game::load::Rounds;
+use crate::game::timer::QuestionTimer;
+use crate::game::survival::{self, Lives, PendingQuestions};
+use crate::game::hotseat::{self, HotSeat, Turn, TurnPassed};
 
 // Hardcoded for now for predetermined screen size
 const OFFSET_X: f32 = -400.;
 const OFFSET_Y: f32 = -300.;
+// Shown in place of the question while survival waits on its next batch
+const AWAITING_TEXT: &str = "Loading more questions. . .";
 
 pub struct CheckPlugin;
 
 #[derive(Component)]
 pub struct SubmitButton;
 #[derive(Default, Component)]
-pub struct AnswerBlock;
+pub struct AnswerBlock(pub usize);
 #[derive(Component)]
 struct AnswerBorder;
 #[derive(Component)]
@@ -27,15 +37,43 @@ pub struct Truth(pub bool);
 #[derive(Component)]
 pub struct QuestionSlot;
 #[derive(Component)]
-pub struct AnswerSlot;
+pub struct AnswerGrid;
+#[derive(Component)]
+pub struct AnswerSlot(pub usize);
 #[derive(Component)]
 pub struct QuestionText;
 #[derive(Component)]
-struct Highlight {
+pub struct Highlight {
     timer: Timer,
     remain: u8,
 }
 
+// How the player wagered on a single question, and what it earned them
+pub struct RoundWagers {
+    pub wagers: Vec<usize>,
+    pub correct: usize,
+    pub points: i32,
+}
+
+// Every round of the current game, recorded as each is submitted
+#[derive(Default)]
+pub struct WagerHistory(pub Vec<RoundWagers>);
+
+// The answer picked out for placing tokens without the mouse, by its index
+#[derive(Default)]
+pub struct AnswerFocus(pub Option<usize>);
+#[derive(Component)]
+struct FocusRing;
+
+// Ways the AnswerFocus can be moved around the AnswerGrid
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FocusMove {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
 #[derive(Default, Bundle)]
 struct AnswerBundle {
     answer_block: AnswerBlock,
@@ -46,27 +84,82 @@ struct AnswerBundle {
     global_transform: GlobalTransform,
 }
 
-struct SubmitPressed;
+pub struct SubmitPressed;
 struct NewRound;
 
 impl Plugin for CheckPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<SubmitPressed>()
            .add_event::<NewRound>()
+           .init_resource::<WagerHistory>()
+           .init_resource::<AnswerFocus>()
+           .add_system_set(
+               SystemSet::on_enter(AppState::Game).with_system(reset_history)
+                                                  .with_system(reset_focus))
            .add_system_set(
                SystemSet::on_update(AppState::Game).with_system(spawn_questionblock)
+                                                   .with_system(layout_answers)
                                                    .with_system(spawn_answerblock)
                                                    .with_system(submit_button)
                                                    .with_system(submit_visible)
                                                    .with_system(submit_tokens)
                                                    .with_system(highlight_correct)
                                                    .with_system(update_round)
-                                                   .with_system(update_q_and_a))
+                                                   .with_system(update_q_and_a)
+                                                   .with_system(show_focus))
            .add_system_set(
                SystemSet::on_exit(AppState::Game).with_system(teardown_blocks));
     }
 }
 
+// Clears the wagers left over from any previous game
+fn reset_history(mut history: ResMut<WagerHistory>) {
+    history.0.clear();
+}
+
+fn reset_focus(mut focus: ResMut<AnswerFocus>) {
+    focus.0 = None;
+}
+
+// Moves the focus to index, only marking AnswerFocus changed if it actually moves
+pub fn set_focus(focus: &mut ResMut<AnswerFocus>, index: Option<usize>) {
+    if focus.0 != index {
+        focus.0 = index;
+    }
+}
+
+// Answers laid out in each row of the AnswerGrid. Up to four answers fit two to a
+// row, any more three to a row
+pub fn answer_columns(answer_count: usize) -> usize {
+    if answer_count <= 4 { 2 } else { 3 }
+}
+
+// Where the focus lands when moved by step among answer_count answers, staying put
+// at the edges of the AnswerGrid. With nothing focused, the first answer is
+pub fn move_focus(focus: Option<usize>, step: FocusMove, answer_count: usize) -> Option<usize> {
+    let columns = answer_columns(answer_count);
+    let index = match focus {
+        Some(index) if index < answer_count => index,
+        _ => return (answer_count > 0).then_some(0),
+    };
+
+    Some(match step {
+        FocusMove::Left if index > 0 => index - 1,
+        FocusMove::Right if index + 1 < answer_count => index + 1,
+        FocusMove::Up if index >= columns => index - columns,
+        FocusMove::Down if index + columns < answer_count => index + columns,
+        _ => index,
+    })
+}
+
+// Submits the wagers, hiding and locking the cursor until the next question
+pub fn press_submit(windows: &mut Windows, submit_pressed: &mut EventWriter<SubmitPressed>) {
+    let window = windows.get_primary_mut().unwrap();
+    window.set_cursor_visibility(false);
+    window.set_cursor_lock_mode(true);
+    submit_pressed.send(SubmitPressed);
+}
+
 // Spawns a 'questionblock' in the QuestionSlot
 fn spawn_questionblock(question_slot: Query<(Entity, &GlobalTransform, &Node), 
                            With<QuestionSlot>>,
@@ -107,17 +200,75 @@ fn spawn_questionblock(question_slot: Query<(Entity, &GlobalTransform, &Node),
     }
 }
 
+// Fills the AnswerGrid with one AnswerSlot per answer to the current Question,
+// clearing away the previous Question's answerblocks
+fn layout_answers(grid_query: Query<(Entity, ChangeTrackers<AnswerGrid>)>,
+                  answer_query: Query<Entity, With<AnswerBlock>>,
+                  rounds: Res<Rounds>,
+                  mut cmds: Commands,
+) {
+    for (grid_id, grid_tracker) in grid_query.iter() {
+        if !(grid_tracker.is_added() || rounds.is_changed()) 
+            || rounds.round_number >= rounds.round_max {
+            continue;
+        }
+
+        for answer_id in answer_query.iter() {
+            cmds.entity(answer_id).despawn_recursive();
+        }
+        cmds.entity(grid_id).despawn_descendants();
+
+        let answer_count = rounds.questions[rounds.round_number].answers.len();
+        let columns = answer_columns(answer_count);
+
+        cmds.entity(grid_id).with_children(|parent| {
+            for row_start in (0..answer_count).step_by(columns) {
+                // Row Answer Container
+                parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::Center,
+                        padding: Rect::all(Val::Px(5.)),
+                        size: Size::new(Val::Percent(100.), Val::Percent(50.)),
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                }).with_children(|parent| {
+                    for i in row_start..(row_start + columns).min(answer_count) {
+                        // Answer Box
+                        parent.spawn_bundle(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                margin: Rect::all(Val::Px(5.)),
+                                padding: Rect::all(Val::Px(5.)),
+                                size: Size::new(Val::Percent(100. / columns as f32),
+                                                Val::Percent(100.)),
+                                ..Default::default()
+                            },
+                            color: Color::NONE.into(),
+                            ..Default::default()
+                        }).insert(AnswerSlot(i));
+                    }
+                });
+            }
+        });
+    }
+}
+
 // Spawns an 'answerblock' in every AnswerSlot
-fn spawn_answerblock(answer_slots: Query<(Entity, &GlobalTransform, &Node),
-                        With<AnswerSlot>>,
+fn spawn_answerblock(answer_slots: Query<(Entity, &GlobalTransform, &Node, &AnswerSlot)>,
                      asset_server: Res<AssetServer>,
                      rounds: Res<Rounds>,
                      mut cmds: Commands,
 ) {
     let palette = [AnswerColor(Color::RED), AnswerColor(Color::GREEN), 
-                   AnswerColor(Color::rgb(0.117, 0.470, 0.823)), AnswerColor(Color::YELLOW)];
+                   AnswerColor(Color::rgb(0.117, 0.470, 0.823)), AnswerColor(Color::YELLOW),
+                   AnswerColor(Color::ORANGE), AnswerColor(Color::VIOLET)];
+
+    for (slot_id, answer_gt, answer_node, answer_slot) in answer_slots.iter() {
+        let i = answer_slot.0;
 
-    for (i, (slot_id, answer_gt, answer_node)) in answer_slots.iter().enumerate() {
         // Mega scuffed, but only way around my poor programming and Bevy's poor
         // frame-update dispatch decisions
         let question = &rounds.questions[rounds.round_number];
@@ -127,7 +278,7 @@ fn spawn_answerblock(answer_slots: Query<(Entity, &GlobalTransform, &Node),
 
             // Whole Bundle
             cmds.spawn_bundle(AnswerBundle {
-                answer_block: AnswerBlock,
+                answer_block: AnswerBlock(i),
                 color: palette[i],
                 truth: Truth(question.answers[i].truth),
                 side_length: SideLength {
@@ -194,23 +345,52 @@ fn spawn_answerblock(answer_slots: Query<(Entity, &GlobalTransform, &Node),
     }
 }
 
+// Outlines the focused answerblock, following it as answerblocks are rebuilt
+fn show_focus(answer_query: Query<(Entity, &AnswerBlock, &SideLength)>,
+              added_query: Query<Entity, Added<AnswerBlock>>,
+              ring_query: Query<Entity, With<FocusRing>>,
+              focus: Res<AnswerFocus>,
+              mut cmds: Commands,
+) {
+    if !focus.is_changed() && added_query.is_empty() {
+        return;
+    }
+
+    for ring_id in ring_query.iter() {
+        cmds.entity(ring_id).despawn_recursive();
+    }
+
+    for (block_id, answer_block, sides) in answer_query.iter() {
+        if focus.0 == Some(answer_block.0) {
+            cmds.entity(block_id).with_children(|parent| {
+                parent.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(0.1, 0.1, 0.5),
+                        custom_size: Some(Vec2::new(sides.x_len + 15., sides.y_len + 15.)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0., 0., -0.5),
+                    ..Default::default()
+                }).insert(FocusRing);
+            });
+        }
+    }
+}
+
 // Click handler for hitting the submit button
+#[allow(clippy::type_complexity)]
 fn submit_button(mut submit_pressed: EventWriter<SubmitPressed>,
-                 mut submit_query: Query<(&Visibility, &Interaction, &mut UiColor),
-                                         (Changed<Interaction>, With<SubmitButton>)>,
+                 mut submit_query: Query<(&Visibility, &Interaction, Option<&PadClick>,
+                                          &mut UiColor), (Pressed, With<SubmitButton>)>,
                  button_colors: Res<ButtonMaterials>,
                  mut windows: ResMut<Windows>,
 ) {
-    for (visibility, interaction, mut color) in submit_query.iter_mut() {
+    for (visibility, interaction, pad_click, mut color) in submit_query.iter_mut() {
         if visibility.is_visible {
-            let window = windows.get_primary_mut().unwrap();
-
-            match interaction {
+            match gamepad::interaction(interaction, pad_click) {
                 Interaction::Clicked => {
                     *color = button_colors.clicked;
-                    window.set_cursor_visibility(false);
-                    window.set_cursor_lock_mode(true);
-                    submit_pressed.send(SubmitPressed);
+                    press_submit(&mut windows, &mut submit_pressed);
                 },
                 Interaction::Hovered => {
                     *color = button_colors.hovered;
@@ -227,38 +407,79 @@ fn submit_button(mut submit_pressed: EventWriter<SubmitPressed>,
 fn submit_visible(token_query: Query<Option<&On>, With<Token>>,
                   mut submit_query: Query<&mut Visibility,
                       With<SubmitButton>>,
+                  settings: Res<GameSettings>,
 ) {
+    // Every one of the player's tokens must be placed, including any yet to spawn
+    let placed = token_query.iter().filter(|on| on.is_some()).count();
+    let all_placed = placed == settings.token_count 
+        && token_query.iter().filter(|on| on.is_none()).count() == 0;
+
     for mut submit_visibility in submit_query.iter_mut() {
-        if submit_visibility.is_visible {
-            if token_query.iter().filter(|on| on.is_none()).count() != 0 {
-                submit_visibility.is_visible = false;
-            }
-        } else {
-            if token_query.iter().filter(|on| on.is_none()).count() == 0 {
-                submit_visibility.is_visible = true;
-            }
+        if submit_visibility.is_visible != all_placed {
+            submit_visibility.is_visible = all_placed;
         }
     }
 }
 
-// Determines whether tokens were on a correct or incorrect answer when submitted
+// Scores the tokens wagered on each answer when submitted, by the chosen ScoringRule.
+// In hot seat the board is passed on until every player has wagered
+#[allow(clippy::too_many_arguments)]
 fn submit_tokens(mut submit_pressed: EventReader<SubmitPressed>,
                  tokens: Query<&On, With<Token>>,
-                 answer_blocks: Query<(Entity, &Truth), With<AnswerBlock>>,
+                 answer_blocks: Query<(Entity, &AnswerBlock, &Truth)>,
                  mut score_count: Query<(&mut Text, &mut ScoreCount)>,
+                 rounds: Res<Rounds>,
+                 settings: Res<GameSettings>,
+                 question_timer: Res<QuestionTimer>,
+                 mut history: ResMut<WagerHistory>,
+                 mut hot_seat: ResMut<HotSeat>,
+                 mut turn_passed: EventWriter<TurnPassed>,
                  mut cmds: Commands,
 ) {
     if submit_pressed.iter().last().is_some() {
-        let mut correct = 0;
+        let question = &rounds.questions[rounds.round_number];
+        let mut wagers = vec![0; question.answers.len()];
         for token_on in tokens.iter() {
-            if let Ok((_, answer_truth)) = answer_blocks.get(token_on.0) {
-                if answer_truth.0 {
-                    correct += 1;
-                }
+            if let Ok((_, answer_block, _)) = answer_blocks.get(token_on.0) {
+                wagers[answer_block.0] += 1;
             }
         }
-        
-        for (answer_id, answer_truth) in answer_blocks.iter() {
+        let correct = question.answers.iter()
+            .position(|answer| answer.truth)
+            .unwrap_or_default();
+
+        let (mut text, mut score) = score_count.single_mut();
+        if settings.mode == GameMode::HotSeat {
+            hot_seat.turns.push(Turn {
+                wagers,
+                remaining: question_timer.remaining(),
+            });
+            if hot_seat.turns.len() < hot_seat.scores.len() {
+                hot_seat.current += 1;
+                turn_passed.send(TurnPassed);
+                return;
+            }
+
+            let turns = std::mem::take(&mut hot_seat.turns);
+            for (player, turn) in turns.iter().enumerate() {
+                hot_seat.scores[player] += points(&turn.wagers, correct, turn.remaining,
+                                                  &settings);
+            }
+            hot_seat.current = 0;
+            text.sections[0].value = hotseat::scoreboard(&hot_seat.scores);
+        } else {
+            let points = points(&wagers, correct, question_timer.remaining(), &settings);
+            history.0.push(RoundWagers {
+                wagers,
+                correct,
+                points,
+            });
+
+            score.0 += points;
+            text.sections[0].value = format!("Score: {}", score.0);
+        }
+
+        for (answer_id, _, answer_truth) in answer_blocks.iter() {
             if answer_truth.0 {
                 cmds.entity(answer_id).insert(Highlight {
                     timer: Timer::from_seconds(0.5, true),
@@ -266,24 +487,47 @@ fn submit_tokens(mut submit_pressed: EventReader<SubmitPressed>,
                 }); 
             }
         }
+    }
+}
 
-        let (mut text, mut score) = score_count.single_mut();
-        score.0 += correct;
-        text.sections[0].value = format!("Score: {}", score.0);
+// Points earned by wagers, with remaining the fraction of the time limit left over
+pub fn points(wagers: &[usize], correct: usize, remaining: f32, settings: &GameSettings) -> i32 {
+    let mut points = settings.scoring.score(wagers, correct, settings.token_count);
+
+    // In timed mode, a correct wager earns up to half as much again for speed
+    if settings.time_limit.is_some() && settings.time_bonus && wagers[correct] > 0 {
+        let max_bonus = settings.scoring.max_score(settings.token_count) as f32 / 2.;
+        points += (max_bonus * remaining).round() as i32;
     }
+
+    points
 }
 
-// Plays a simple animation around correct answer, then signals a new round
+// Plays a simple animation around correct answer, then signals a new round. Survival
+// holds the board here, cursor still locked, while more questions are on their way
+#[allow(clippy::too_many_arguments)]
 fn highlight_correct(mut highlight_query: Query<(Entity, &Children, &mut Highlight)>,
                      mut border_query: Query<&mut Sprite, With<AnswerBorder>>,
+                     mut question_text: Query<&mut Text, With<QuestionText>>,
                      mut new_round: EventWriter<NewRound>,
                      mut windows: ResMut<Windows>,
+                     rounds: Res<Rounds>,
+                     pending: Res<PendingQuestions>,
+                     lives: Res<Lives>,
+                     settings: Res<GameSettings>,
                      time: Res<Time>,
                      mut cmds: Commands,
 ) {
     for (hl_id, hl_children, mut hl) in highlight_query.iter_mut() {
         if hl.timer.tick(time.delta()).just_finished() {
-            if hl.remain == 0 {
+            if hl.remain == 0
+               && survival::awaiting_questions(&rounds, &pending, &lives, &settings) {
+                for mut text in question_text.iter_mut() {
+                    if text.sections[0].value != AWAITING_TEXT {
+                        text.sections[0].value = String::from(AWAITING_TEXT);
+                    }
+                }
+            } else if hl.remain == 0 {
                 // Animating done, unlock mouse and start new round
                 cmds.entity(hl_id).remove::<Highlight>();
                 new_round.send(NewRound);
@@ -311,47 +555,53 @@ fn highlight_correct(mut highlight_query: Query<(Entity, &Children, &mut Highlig
 // Updates internal round counter and QuestionCount text
 fn update_round(mut new_round: EventReader<NewRound>,
                 mut rounds: ResMut<Rounds>,
+                mut pending: ResMut<PendingQuestions>,
                 mut q_count: Query<(&mut Text, &mut QuestionCount)>,
+                lives: Res<Lives>,
+                settings: Res<GameSettings>,
 ) {
     if new_round.iter().last().is_some() {
         rounds.round_number += 1;
+
+        // Survival ends with the last life, or once the Source has run dry, and
+        // otherwise carries on into the questions fetched in the meantime
+        if settings.mode == GameMode::Survival {
+            if lives.remaining == 0 {
+                rounds.round_max = rounds.round_number;
+            } else {
+                rounds.questions.append(&mut pending.0);
+                rounds.round_max = rounds.questions.len();
+            }
+        }
         
         if rounds.round_number < rounds.round_max {
             let (mut text, mut question) = q_count.single_mut();
             question.0 += 1;
-            text.sections[0].value = format!("Question: {}/8", question.0);
+            text.sections[0].value = question_count_text(question.0, &rounds, &settings);
         }
     }
 }
 
-// Updates QuestionText and AnswerText for a new rounds when SubmitPressed
-fn update_q_and_a(mut qa_text: ParamSet<(
-                      Query<&mut Text, With<QuestionText>>,
-                      Query<&mut Text, With<AnswerText>>,
-                  )>,
-                  answerborder_family: Query<(&Parent, &Children), With<AnswerBorder>>,
-                  mut truths: Query<&mut Truth>,
+// Text for the QuestionCount, leaving out the total in survival where there is none
+pub fn question_count_text(question: u32, rounds: &Rounds, settings: &GameSettings) -> String {
+    match settings.mode {
+        GameMode::Standard | GameMode::Daily | GameMode::HotSeat | GameMode::Versus => {
+            format!("Question: {}/{}", question, rounds.round_max)
+        },
+        GameMode::Survival => format!("Question: {}", question),
+    }
+}
+
+// Updates QuestionText for a new round; answerblocks are rebuilt by layout_answers
+#[allow(clippy::type_complexity)]
+fn update_q_and_a(mut question_text: Query<&mut Text, With<QuestionText>>,
                   rounds: Res<Rounds>,
 ) {
     if rounds.is_changed() && rounds.round_number < rounds.round_max {
         let new_q = &rounds.questions[rounds.round_number];
 
-        // Update QuestionText 
-        for mut question_text in qa_text.p0().iter_mut() {
-            question_text.sections[0].value = new_q.text.clone();
-        }
-        
-        // Update AnswerText and Truth
-        for (i, (ab_parent, ab_children)) in answerborder_family.iter().enumerate() {
-            if let Ok(mut parent_truth) = truths.get_mut(ab_parent.0) {
-                parent_truth.0 = new_q.answers[i].truth;
-            }
-
-            for &child in ab_children.iter() {
-                if let Ok(mut child_text) = qa_text.p1().get_mut(child) {
-                    child_text.sections[0].value = new_q.answers[i].text.clone();
-                }
-            }
+        for mut text in question_text.iter_mut() {
+            text.sections[0].value = new_q.text.clone();
         }
     }
 }
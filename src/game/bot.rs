@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use std::fmt;
+
+use crate::AppState;
+use crate::setup::{GameMode, GameSettings};
+use crate::game::answer::{self, AnswerBlock, SubmitPressed};
+use crate::game::load::Rounds;
+use crate::game::token::SideLength;
+
+pub struct BotPlugin;
+
+// How the bot weighs its hunches when wagering
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BotProfile {
+    // Stakes far more on its favourite than its accuracy warrants
+    OverConfident,
+    // Stakes on its favourite exactly as often as it turns out right
+    Calibrated,
+    // Spreads its tokens more evenly than its accuracy warrants
+    Hedging,
+}
+
+// The bot's running score, and its wagers on the current question
+#[derive(Default)]
+pub struct Bot {
+    pub score: i32,
+    wagers: Vec<usize>,
+}
+
+#[derive(Component)]
+pub struct BotScore;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bot>()
+           .add_system_set(
+               SystemSet::on_enter(AppState::Game).with_system(reset_bot))
+           .add_system_set(
+               SystemSet::on_update(AppState::Game).with_system(bot_wager)
+                                                   .with_system(bot_submit)
+                                                   .with_system(update_bot_score));
+    }
+}
+
+impl BotProfile {
+    pub const ALL: [BotProfile; 3] = [BotProfile::OverConfident, BotProfile::Calibrated,
+                                      BotProfile::Hedging];
+
+    // Share of its tokens the bot stakes on its favourite answer, given the chance
+    // that answer is correct
+    fn confidence(&self, accuracy: f32, answer_count: usize) -> f32 {
+        match self {
+            BotProfile::OverConfident => (accuracy + 0.35).min(1.),
+            BotProfile::Calibrated => accuracy,
+            BotProfile::Hedging => (accuracy + 1. / answer_count as f32) / 2.,
+        }
+    }
+}
+
+impl fmt::Display for BotProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotProfile::OverConfident => write!(f, "Over-Confident"),
+            BotProfile::Calibrated => write!(f, "Well-Calibrated"),
+            BotProfile::Hedging => write!(f, "Hedging"),
+        }
+    }
+}
+
+// Chance the bot's favourite answer is correct, as set in percent, or otherwise by
+// how hard the question is
+fn accuracy(percent: Option<u32>, difficulty: &str, answer_count: usize) -> f32 {
+    let known = match (percent, difficulty) {
+        (Some(percent), _) => percent as f32 / 100.,
+        (None, "easy") => 0.75,
+        (None, "hard") => 0.35,
+        (None, _) => 0.55,
+    };
+
+    // Even a blind guess is right this often
+    f32::max(known, 1. / answer_count as f32)
+}
+
+// Splits token_count between answers in proportion to beliefs, handing leftover
+// tokens to whichever answers came closest to earning another
+fn apportion(beliefs: &[f32], token_count: usize) -> Vec<usize> {
+    let shares: Vec<f32> = beliefs.iter().map(|belief| belief * token_count as f32).collect();
+    let mut wagers: Vec<usize> = shares.iter().map(|share| share.floor() as usize).collect();
+
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| shares[b].fract().total_cmp(&shares[a].fract()));
+    let leftover = token_count.saturating_sub(wagers.iter().sum());
+    for &i in order.iter().take(leftover) {
+        wagers[i] += 1;
+    }
+
+    wagers
+}
+
+fn reset_bot(mut bot: ResMut<Bot>) {
+    bot.score = 0;
+    bot.wagers.clear();
+}
+
+// Decides the bot's wagers as each question begins, out of the player's sight
+fn bot_wager(mut bot: ResMut<Bot>, rounds: Res<Rounds>, settings: Res<GameSettings>) {
+    if settings.mode != GameMode::Versus || !rounds.is_changed()
+       || rounds.round_number >= rounds.round_max {
+        return;
+    }
+
+    let question = &rounds.questions[rounds.round_number];
+    let answer_count = question.answers.len();
+    let accuracy = accuracy(settings.bot_accuracy, &question.difficulty, answer_count);
+    let correct = question.answers.iter()
+        .position(|answer| answer.truth)
+        .unwrap_or_default();
+
+    // The bot favours the correct answer as often as its accuracy allows, and
+    // otherwise some wrong answer
+    let mut rng = rand::thread_rng();
+    let favourite = if rng.gen::<f32>() < accuracy {
+        correct
+    } else {
+        let wrong = rng.gen_range(0..answer_count - 1);
+        if wrong >= correct { wrong + 1 } else { wrong }
+    };
+
+    let confidence = settings.bot.confidence(accuracy, answer_count);
+    let beliefs: Vec<f32> = (0..answer_count)
+        .map(|i| if i == favourite {
+            confidence
+        } else {
+            (1. - confidence) / (answer_count - 1) as f32
+        })
+        .collect();
+
+    bot.wagers = apportion(&beliefs, settings.token_count);
+}
+
+// Scores the bot alongside the player, and reveals its wagers on each answerblock
+fn bot_submit(mut submit_pressed: EventReader<SubmitPressed>,
+              answer_blocks: Query<(Entity, &AnswerBlock, &SideLength)>,
+              mut bot: ResMut<Bot>,
+              rounds: Res<Rounds>,
+              settings: Res<GameSettings>,
+              asset_server: Res<AssetServer>,
+              mut cmds: Commands,
+) {
+    if submit_pressed.iter().last().is_none() || settings.mode != GameMode::Versus {
+        return;
+    }
+
+    let question = &rounds.questions[rounds.round_number];
+    let correct = question.answers.iter()
+        .position(|answer| answer.truth)
+        .unwrap_or_default();
+    // The bot answers instantly, so earns no time bonus
+    bot.score += answer::points(&bot.wagers, correct, 0., &settings);
+
+    for (block_id, answer_block, side_length) in answer_blocks.iter() {
+        let wager = bot.wagers.get(answer_block.0).copied().unwrap_or_default();
+        cmds.entity(block_id).with_children(|parent| {
+            parent.spawn_bundle(Text2dBundle {
+                transform: Transform::from_xyz(0., side_length.y_len / 2. - 15., 2.),
+                text: Text::with_section(
+                    format!("Bot: {}", wager),
+                    TextStyle {
+                        font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                        font_size: 20.,
+                        color: Color::BLACK,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                ..Default::default()
+            });
+        });
+    }
+}
+
+// Keeps the BotScore text in sync with the bot's score
+fn update_bot_score(mut score_query: Query<(&mut Text, ChangeTrackers<BotScore>)>,
+                    bot: Res<Bot>,
+) {
+    for (mut text, tracker) in score_query.iter_mut() {
+        if bot.is_changed() || tracker.is_added() {
+            text.sections[0].value = format!("Bot: {}", bot.score);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apportion_even_split() {
+        assert_eq!(apportion(&[0.25; 4], 8), [2, 2, 2, 2]);
+        assert_eq!(apportion(&[1., 0., 0.], 5), [5, 0, 0]);
+    }
+
+    #[test]
+    fn apportion_leftovers_go_to_the_largest_fractions() {
+        // Shares of 2.25, 1.75 and 1 leave one token for the second answer
+        assert_eq!(apportion(&[0.45, 0.35, 0.2], 5), [2, 2, 1]);
+        assert_eq!(apportion(&[0.1, 0.65, 0.25], 4), [0, 3, 1]);
+    }
+
+    #[test]
+    fn apportion_places_every_token() {
+        for token_count in 1..=10 {
+            for beliefs in [[1. / 3.; 3], [0.7, 0.2, 0.1], [0.05, 0.05, 0.9]] {
+                let wagers = apportion(&beliefs, token_count);
+                assert_eq!(wagers.iter().sum::<usize>(), token_count);
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use futures_lite::future;
+
+use crate::AppState;
+use crate::setup::{GameMode, GameSettings};
+use crate::storage::{civil_date, unix_time, write_text};
+use crate::game::answer::WagerHistory;
+use crate::game::load::Rounds;
+use crate::game::ui::ScoreCount;
+
+use std::fmt;
+use std::path::PathBuf;
+
+pub struct DailyPlugin;
+
+// Sent with the path the day's summary was saved to, once it has been written
+pub struct DailyExported(pub PathBuf);
+
+// Where this game's daily summary is in being saved, so it's only written once
+#[derive(Default)]
+enum DailyExport {
+    #[default]
+    Pending,
+    Writing(Task<Option<PathBuf>>),
+    Done,
+}
+
+// The day in UTC a daily challenge was drawn for, which still names its summary
+// should the game run on past midnight
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DailyDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DailyExported>()
+           .init_resource::<DailyExport>()
+           .add_system_set(
+               SystemSet::on_enter(AppState::Game).with_system(reset_export))
+           .add_system_set(
+               SystemSet::on_update(AppState::Game).with_system(export_daily)
+                                                   .with_system(await_export));
+    }
+}
+
+impl DailyDate {
+    pub fn today() -> DailyDate {
+        let (year, month, day) = civil_date(unix_time());
+        DailyDate { year, month, day }
+    }
+
+    // Seeds the day's questions, so they change at the same moment for everyone
+    pub fn seed(&self) -> u64 {
+        self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64
+    }
+}
+
+impl fmt::Display for DailyDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// A short, spoiler-free summary of a daily challenge, one square per question
+// shaded by how many tokens landed on the correct answer
+pub fn summary(date: DailyDate,
+               score: i32,
+               max_score: i32,
+               history: &WagerHistory,
+               settings: &GameSettings,
+) -> String {
+    let squares: String = history.0.iter()
+        .map(|round| {
+            let placed = round.wagers[round.correct];
+            if placed == settings.token_count {
+                '🟩'
+            } else if placed * 2 >= settings.token_count {
+                '🟨'
+            } else if placed > 0 {
+                '🟧'
+            } else {
+                '⬛'
+            }
+        })
+        .collect();
+
+    format!("PrettySure Daily {}\n{} of {} Points ({} tokens, {})\n{}\n",
+            date, score, max_score, settings.token_count, settings.scoring.name(),
+            squares)
+}
+
+// Saves the summary of date's challenge to the data directory, returning where it
+// was written
+pub fn export_summary(date: DailyDate, summary: &str) -> Option<PathBuf> {
+    write_text(&format!("daily_{}.txt", date), summary)
+}
+
+fn reset_export(mut export: ResMut<DailyExport>) {
+    *export = DailyExport::Pending;
+}
+
+// Leaves writing the summary of a finished daily challenge to the IoTaskPool
+fn export_daily(score_q: Query<&ScoreCount>,
+                rounds: Res<Rounds>,
+                history: Res<WagerHistory>,
+                settings: Res<GameSettings>,
+                daily_date: Option<Res<DailyDate>>,
+                thread_pool: Res<IoTaskPool>,
+                mut export: ResMut<DailyExport>,
+) {
+    let finished = rounds.round_number == rounds.round_max;
+    if settings.mode != GameMode::Daily || !finished
+        || !matches!(*export, DailyExport::Pending) {
+        return;
+    }
+
+    let date = daily_date.map_or_else(DailyDate::today, |date| *date);
+    let max_score = rounds.round_max as i32
+        * settings.scoring.max_score(settings.token_count);
+    let summary = summary(date, score_q.single().0, max_score, &history, &settings);
+    info!("Daily challenge summary:\n{}", summary);
+
+    let task = thread_pool.spawn(async move {
+        export_summary(date, &summary)
+    });
+    *export = DailyExport::Writing(task);
+}
+
+// Awaits the summary being written and passes on where it was saved
+fn await_export(mut export: ResMut<DailyExport>,
+                mut exported: EventWriter<DailyExported>,
+) {
+    if let DailyExport::Writing(task) = &mut *export {
+        if let Some(path) = future::block_on(future::poll_once(task)) {
+            if let Some(path) = path {
+                exported.send(DailyExported(path));
+            }
+            *export = DailyExport::Done;
+        }
+    }
+}
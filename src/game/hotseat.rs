@@ -0,0 +1,205 @@
+use bevy::prelude::*;
+
+use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::setup::{GameMode, GameSettings};
+
+pub struct HotSeatPlugin;
+
+// Scores for every player in a hot seat game, along with the wagers already made on
+// the current question
+#[derive(Default)]
+pub struct HotSeat {
+    pub current: usize,
+    pub scores: Vec<i32>,
+    pub turns: Vec<Turn>,
+}
+
+// One player's wagers on the current question, held until everyone has wagered
+pub struct Turn {
+    pub wagers: Vec<usize>,
+    pub remaining: f32,
+}
+
+// Sent when a player submits and the board must be handed to the next
+pub struct TurnPassed;
+// Sent once the next player is ready to wager
+pub struct TurnStarted;
+
+// Covers the board while it is handed from one player to the next
+#[derive(Component)]
+pub struct PassScreen;
+#[derive(Component)]
+struct ReadyButton;
+#[derive(Component)]
+pub struct TurnText;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HotSeat>()
+           .add_event::<TurnPassed>()
+           .add_event::<TurnStarted>()
+           .add_system_set(
+               SystemSet::on_enter(AppState::Game).with_system(reset_hot_seat))
+           .add_system_set(
+               SystemSet::on_update(AppState::Game).with_system(spawn_pass_screen)
+                                                   .with_system(ready_button)
+                                                   .with_system(update_turn_text))
+           .add_system_set(
+               SystemSet::on_exit(AppState::Game).with_system(teardown_pass_screen));
+    }
+}
+
+// Every player's score, one to a line
+pub fn scoreboard(scores: &[i32]) -> String {
+    scores.iter()
+        .enumerate()
+        .map(|(player, score)| format!("Player {}: {}", player + 1, score))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Names whoever finished with the highest score, or everyone who shares it
+pub fn winner_text(scores: &[i32]) -> String {
+    let best = scores.iter().copied().max().unwrap_or_default();
+    let winners: Vec<String> = scores.iter()
+        .enumerate()
+        .filter(|(_, &score)| score == best)
+        .map(|(player, _)| (player + 1).to_string())
+        .collect();
+
+    match winners.as_slice() {
+        [winner] => format!("Player {} Wins!", winner),
+        [rest @ .., last] => format!("Players {} and {} Tie!", rest.join(", "), last),
+        [] => String::new(),
+    }
+}
+
+fn reset_hot_seat(mut hot_seat: ResMut<HotSeat>, settings: Res<GameSettings>) {
+    let players = if settings.mode == GameMode::HotSeat { settings.players } else { 1 };
+    *hot_seat = HotSeat {
+        current: 0,
+        scores: vec![0; players],
+        turns: Vec::new(),
+    };
+}
+
+// Hides the board behind a screen naming the next player, freeing the cursor so
+// they can say when they are ready
+fn spawn_pass_screen(mut turn_passed: EventReader<TurnPassed>,
+                     hot_seat: Res<HotSeat>,
+                     asset_server: Res<AssetServer>,
+                     button_colors: Res<ButtonMaterials>,
+                     mut windows: ResMut<Windows>,
+                     mut cmds: Commands,
+) {
+    if turn_passed.iter().last().is_none() {
+        return;
+    }
+
+    let window = windows.get_primary_mut().unwrap();
+    window.set_cursor_visibility(true);
+    window.set_cursor_lock_mode(false);
+
+    cmds.spawn_bundle(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::ColumnReverse,
+            ..Default::default()
+        },
+        color: Color::rgb(0.85, 0.8, 0.95).into(),
+        ..Default::default()
+    })
+    // Stops clicks reaching the board beneath
+    .insert(Interaction::default())
+    .with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: Rect::all(Val::Px(20.)),
+                ..Default::default()
+            },
+            text: Text::with_section(
+                format!("Pass to Player {}", hot_seat.current + 1),
+                TextStyle {
+                    font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                    font_size: 50.,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+
+        parent.spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(150.), Val::Px(50.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: button_colors.none,
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Ready!",
+                    TextStyle {
+                        font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                        font_size: 40.,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        }).insert(ReadyButton);
+    }).insert(PassScreen);
+}
+
+// Click handler for the ready button, revealing the board to the next player
+#[allow(clippy::type_complexity)]
+fn ready_button(mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                 (Pressed, With<ReadyButton>)>,
+                pass_query: Query<Entity, With<PassScreen>>,
+                button_colors: Res<ButtonMaterials>,
+                mut turn_started: EventWriter<TurnStarted>,
+                mut cmds: Commands,
+) {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                for pass_id in pass_query.iter() {
+                    cmds.entity(pass_id).despawn_recursive();
+                }
+                turn_started.send(TurnStarted);
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            },
+        }
+    }
+}
+
+// Keeps the TurnText naming whoever is currently wagering
+fn update_turn_text(mut turn_query: Query<(&mut Text, ChangeTrackers<TurnText>)>,
+                    hot_seat: Res<HotSeat>,
+) {
+    for (mut text, tracker) in turn_query.iter_mut() {
+        if hot_seat.is_changed() || tracker.is_added() {
+            text.sections[0].value = format!("Turn: Player {}", hot_seat.current + 1);
+        }
+    }
+}
+
+fn teardown_pass_screen(pass_query: Query<Entity, With<PassScreen>>, mut cmds: Commands) {
+    for pass_id in pass_query.iter() {
+        cmds.entity(pass_id).despawn_recursive();
+    }
+}
@@ -1,28 +1,48 @@
 use bevy::prelude::*;
-use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy::tasks::{AsyncComputeTaskPool, IoTaskPool, Task};
 use bevy::text::Text2dBounds;
-use serde::Deserialize;
-use html_escape::decode_html_entities;
 use rand::Rng;
-use reqwest::Client;
+use rand::seq::SliceRandom;
 use futures_lite::future;
 use async_compat::Compat;
+use serde::{Deserialize, Serialize};
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Duration;
 
-use crate::AppState;
+use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::setup::{GameMode, GameSettings};
+use crate::game::source::{
+    LoadError,
+    OpenTdb,
+    QuestionQuery,
+    QuestionSource,
+    RawQuestion,
+    Source,
+};
+use crate::game::source::cache;
 
 pub struct LoadPlugin;
 
-#[derive(Default, Component)]
+// Range of answers a Question may have and still fit on the board
+pub const MIN_ANSWERS: usize = 2;
+pub const MAX_ANSWERS: usize = 6;
+// Questions in every daily challenge
+const DAILY_QUESTIONS: usize = 10;
+
+#[derive(Default, Clone, Component, Serialize, Deserialize)]
 pub struct Answer {
     pub text: String,
     pub truth: bool,
 }
-#[derive(Default, Component)]
+#[derive(Default, Clone, Component, Serialize, Deserialize)]
 pub struct Question {
     pub text: String,
-    pub answers: [Answer; 4],
+    pub category: String,
+    pub difficulty: String,
+    pub answers: Vec<Answer>,
 }
 #[derive(Component)]
 struct LoadBar;
@@ -33,6 +53,14 @@ struct LoadText {
 }
 #[derive(Component)]
 struct ErrorCard;
+#[derive(Component)]
+enum ErrorButton {
+    Retry,
+    Menu,
+}
+// Which attempt at retrieving questions the load task is currently on
+#[derive(Component)]
+struct LoadAttempt(Arc<AtomicU32>);
 
 #[derive(Default)]
 pub struct Rounds {
@@ -42,44 +70,33 @@ pub struct Rounds {
 }
 #[derive(Default)]
 pub struct SessionId {
-    id: Option<String>,
+    pub id: Option<String>,
 }
 #[derive(Default)]
-struct SiteData {
-    session_id: SessionId,
-    rounds: Rounds,
+pub struct SiteData {
+    pub session_id: SessionId,
+    pub rounds: Rounds,
 }
 
-struct GetError; 
-
-#[derive(Deserialize)]
-struct ResponseCode(u8);
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct ApiQuestion {
-    category: String,
-    r#type: String,
-    difficulty: String,
-    question: String,
-    correct_answer: String,
-    incorrect_answers: Vec<String>,
-}
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct ApiQResponse {
-    response_code: ResponseCode,
-    results: Vec<ApiQuestion>,
-}
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct ApiIdResponse {
-    response_code: ResponseCode,
-    response_message: String,
-    token: String,
+struct GetError(LoadError);
+
+// How the load task retries fetches which fail for transient reasons
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
 }
 
 impl Plugin for LoadPlugin {
     fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Source>() {
+            app.insert_resource(Source(Arc::new(OpenTdb::default())));
+        }
+        if !app.world.contains_resource::<RetryPolicy>() {
+            app.insert_resource(RetryPolicy::default());
+        }
+
         app.insert_resource(SessionId {id: None})
            .add_event::<GetError>()
            .add_system_set(
@@ -89,27 +106,196 @@ impl Plugin for LoadPlugin {
                SystemSet::on_update(AppState::Load).with_system(insert_trivia)
                                                    .with_system(update_loadscreen)
                                                    .with_system(spawn_errorcard)
-                                                   .with_system(error_to_menu))
+                                                   .with_system(error_buttons))
            .add_system_set(
-               SystemSet::on_exit(AppState::Load).with_system(teardown_loadscreen));
+               SystemSet::on_exit(AppState::Load).with_system(teardown_loadscreen))
+           .add_system_set(
+               SystemSet::on_exit(AppState::Game).with_system(mark_played));
+    }
+}
+
+impl Default for RetryPolicy {
+    // OpenTDB allows one request every five seconds, so never retry sooner than that
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Question {
+    // Builds a Question with its answers placed in a random order, save for true or
+    // false questions which always read True then False
+    pub fn new(raw: &RawQuestion) -> Question {
+        Question::with_rng(raw, &mut rand::thread_rng())
+    }
+
+    // Builds a Question as new does, shuffling its answers with rng
+    pub fn with_rng<R: Rng + ?Sized>(raw: &RawQuestion, rng: &mut R) -> Question {
+        let mut answers: Vec<Answer> = raw.incorrect_answers.iter()
+            .map(|text| Answer {
+                text: text.clone(),
+                truth: false,
+            })
+            .collect();
+        answers.push(Answer {
+            text: raw.correct_answer.clone(),
+            truth: true,
+        });
+
+        let is_boolean = answers.len() == 2
+            && answers.iter().any(|answer| answer.text == "True")
+            && answers.iter().any(|answer| answer.text == "False");
+        if is_boolean {
+            answers.sort_by_key(|answer| answer.text != "True");
+        } else {
+            answers.shuffle(rng);
+        }
+
+        Question {
+            text: raw.question.clone(),
+            category: raw.category.clone(),
+            difficulty: raw.difficulty.clone(),
+            answers,
+        }
+    }
+
+    // Recovers the unshuffled form of the Question
+    pub fn to_raw(&self) -> RawQuestion {
+        RawQuestion {
+            category: self.category.clone(),
+            difficulty: self.difficulty.clone(),
+            question: self.text.clone(),
+            correct_answer: self.answers.iter()
+                                .find(|answer| answer.truth)
+                                .map(|answer| answer.text.clone())
+                                .unwrap_or_default(),
+            incorrect_answers: self.answers.iter()
+                                   .filter(|answer| !answer.truth)
+                                   .map(|answer| answer.text.clone())
+                                   .collect(),
+        }
     }
 }
 
-// Spawns an Async call to retrieve trivia data
+// Spawns an Async call to retrieve trivia data from the current Source
 fn spawn_load_task(thread_pool: Res<AsyncComputeTaskPool>,
                     session_id: Res<SessionId>,
+                    source: Res<Source>,
+                    settings: Res<GameSettings>,
+                    retry_policy: Res<RetryPolicy>,
                     mut cmds: Commands,
 ) {
-    let id = session_id.id.clone();
-    let trivia_get = thread_pool.spawn(async move {
-        let site_data = Compat::new(async {
-            retrieve_questions(id).await
-        }).await;
+    let attempt = Arc::new(AtomicU32::new(1));
+    let trivia_get = spawn_fetch(&thread_pool,
+                                 source.0.clone(),
+                                 question_query(&settings, &session_id),
+                                 retry_policy.clone(),
+                                 attempt.clone());
 
-        site_data
-    });
+    cmds.spawn().insert(trivia_get).insert(LoadAttempt(attempt));
+}
 
-    cmds.spawn().insert(trivia_get);
+// The query for a batch of questions suiting settings. The daily challenge ignores
+// any settings which would change which questions are drawn
+pub fn question_query(settings: &GameSettings, session_id: &SessionId) -> QuestionQuery {
+    if settings.mode == GameMode::Daily {
+        return QuestionQuery {
+            amount: DAILY_QUESTIONS,
+            session_id: session_id.id.clone(),
+            ..Default::default()
+        };
+    }
+
+    QuestionQuery {
+        amount: settings.question_count,
+        session_id: session_id.id.clone(),
+        category: settings.category.clone(),
+        difficulty: settings.difficulty,
+        include_true_false: settings.include_true_false,
+        exclude: Vec::new(),
+    }
+}
+
+// Spawns a task fetching questions from source, retrying as the RetryPolicy allows
+// and falling back on the cache should that fail
+pub fn spawn_fetch(thread_pool: &AsyncComputeTaskPool,
+                   source: Arc<dyn QuestionSource>,
+                   query: QuestionQuery,
+                   retry_policy: RetryPolicy,
+                   attempt: Arc<AtomicU32>,
+) -> Task<Result<SiteData, LoadError>> {
+    thread_pool.spawn(async move {
+        let site_res = Compat::new(
+            fetch_with_retry(source.clone(), query.clone(), retry_policy, attempt)
+        ).await;
+
+        cache_or_fallback(source.as_ref(), &query, site_res)
+    })
+}
+
+// Fetches from source, retrying transient failures until the RetryPolicy gives up
+async fn fetch_with_retry(source: Arc<dyn QuestionSource>,
+                          query: QuestionQuery,
+                          retry_policy: RetryPolicy,
+                          attempt: Arc<AtomicU32>,
+) -> Result<SiteData, LoadError> {
+    loop {
+        let current = attempt.load(Ordering::Relaxed);
+        match source.fetch(query.clone()).await {
+            Err(error) if error.is_transient() && current < retry_policy.max_attempts => {
+                tokio::time::sleep(retry_delay(current, &retry_policy)).await;
+                attempt.fetch_add(1, Ordering::Relaxed);
+            },
+            site_res => return site_res,
+        }
+    }
+}
+
+// Caches questions fetched from a remote source, or falls back on unplayed cached
+// questions should the fetch have failed
+fn cache_or_fallback(source: &dyn QuestionSource,
+                     query: &QuestionQuery,
+                     site_res: Result<SiteData, LoadError>,
+) -> Result<SiteData, LoadError> {
+    if !source.is_remote() {
+        return site_res;
+    }
+
+    match site_res {
+        Ok(site_data) => {
+            cache::store(source.name(), &site_data.rounds.questions);
+            Ok(site_data)
+        },
+        Err(error) => match cache::retrieve_unplayed(source.name(), query) {
+            Some(questions) => {
+                info!("Playing cached questions after failed fetch: {}", error);
+                Ok(SiteData {
+                    session_id: SessionId {
+                        id: query.session_id.clone(),
+                    },
+                    rounds: Rounds {
+                        round_number: 0,
+                        round_max: questions.len(),
+                        questions,
+                    },
+                })
+            },
+            None => Err(error),
+        },
+    }
+}
+
+// Exponential backoff from base_delay, capped at max_delay, with up to half again
+// added as jitter
+fn retry_delay(attempt: u32, retry_policy: &RetryPolicy) -> Duration {
+    let delay = retry_policy.base_delay.saturating_mul(2u32.saturating_pow(attempt - 1))
+                                       .min(retry_policy.max_delay);
+    let jitter = delay.mul_f32(rand::thread_rng().gen_range(0. ..0.5));
+
+    delay + jitter
 }
 
 // Spawns some basic loading text
@@ -143,7 +329,7 @@ fn spawn_loadscreen(asset_server: Res<AssetServer>, mut cmds: Commands) {
 
 // Awaits completion of HTTP requests and inserts Rounds (and potentially a SessionId
 // when done
-fn insert_trivia(mut question_task: Query<(Entity, &mut Task<Result<SiteData, ()>>)>,
+fn insert_trivia(mut question_task: Query<(Entity, &mut Task<Result<SiteData, LoadError>>)>,
                  mut error_writer: EventWriter<GetError>,
                  mut session_id: ResMut<SessionId>,
                  mut appstate: ResMut<State<AppState>>,
@@ -154,37 +340,47 @@ fn insert_trivia(mut question_task: Query<(Entity, &mut Task<Result<SiteData, ()
             match site_res {
                 // Site data successfully retrieved
                 Ok(site_data) => {
-                    // Set SessionId if previously unset
-                    if session_id.id.is_none() {
-                        session_id.id = site_data.session_id.id;
-                    }
+                    // Keep SessionId, which may have been replaced or newly set
+                    *session_id = site_data.session_id;
 
                     // Insert Rounds and finish AppState::Load
                     cmds.insert_resource(site_data.rounds);
                     appstate.set(AppState::Game).unwrap();
                 },
                 // Something went wrong along the way
-                Err(_) => {
-                    error_writer.send(GetError);
+                Err(error) => {
+                    error_writer.send(GetError(error));
                 },
             }
 
-            cmds.entity(entity).remove::<Task<Result<SiteData, ()>>>();
+            cmds.entity(entity).remove::<Task<Result<SiteData, LoadError>>>()
+                               .remove::<LoadAttempt>();
         }
     }
 }
 
-// Simply animates the loading text
+// Animates the loading text, noting which retry is underway if the first attempt
+// failed
 fn update_loadscreen(mut load_query: Query<(&mut LoadText, &mut Text)>,
+                     attempt_query: Query<&LoadAttempt>,
+                     retry_policy: Res<RetryPolicy>,
                      time: Res<Time>,
 ) {
+    let status = match attempt_query.iter().next() {
+        Some(attempt) if attempt.0.load(Ordering::Relaxed) > 1 => {
+            format!("Retrying ({}/{})", attempt.0.load(Ordering::Relaxed),
+                    retry_policy.max_attempts)
+        },
+        _ => String::from("Loading"),
+    };
+
     for (mut load, mut text) in load_query.iter_mut() {
         if load.timer.tick(time.delta()).just_finished() {
             text.sections[0].value = match load.dots {
-                0 => String::from("Loading.    "),
-                1 => String::from("Loading. .  "),
-                2 => String::from("Loading. . ."),
-                _ => String::from("Loading     "),
+                0 => format!("{}.    ", status),
+                1 => format!("{}. .  ", status),
+                2 => format!("{}. . .", status),
+                _ => format!("{}     ", status),
             };
             load.dots = (load.dots + 1) % 4;
         }
@@ -196,10 +392,11 @@ fn update_loadscreen(mut load_query: Query<(&mut LoadText, &mut Text)>,
 fn spawn_errorcard(load_query: Query<Entity, With<LoadBar>>,
                    mut error_reader: EventReader<GetError>, 
                    asset_server: Res<AssetServer>,
+                   button_colors: Res<ButtonMaterials>,
                    windows: Res<Windows>,
                    mut cmds: Commands,
 ) {
-    if error_reader.iter().next().is_some() {
+    if let Some(error) = error_reader.iter().next() {
         let window = windows.get_primary().unwrap();
         let x_dim = window.width() / 2.;
         let y_dim = window.height() / 2.;
@@ -233,14 +430,11 @@ fn spawn_errorcard(load_query: Query<Entity, With<LoadBar>>,
             });
 
             parent.spawn_bundle(Text2dBundle {
-                transform: Transform::from_xyz(0., 0., 55.),
+                transform: Transform::from_xyz(0., 30., 55.),
                 text: Text {
                     sections: vec![
                         TextSection {
-                            value: String::from("An error occured while retrieving \
-                                                your trivia questions. Please check \
-                                                your internet connection and try \
-                                                again.\n\n"),
+                            value: format!("{}\n\n", error.0),
                             style: TextStyle {
                                 font: asset_server.load("fonts/PublicSans-Medium.ttf"),
                                 font_size: 24.,
@@ -248,7 +442,7 @@ fn spawn_errorcard(load_query: Query<Entity, With<LoadBar>>,
                             },
                         },
                         TextSection {
-                            value: String::from("Click anywhere to continue..."),
+                            value: String::from(error.0.suggestion()),
                             style: TextStyle {
                                 font: asset_server.load("fonts/PublicSans-Medium.ttf"),
                                 font_size: 24.,
@@ -263,26 +457,106 @@ fn spawn_errorcard(load_query: Query<Entity, With<LoadBar>>,
                     },
                 },
                 text_2d_bounds: Text2dBounds {
-                    size: Size::new(x_dim, y_dim),
+                    size: Size::new(x_dim - 20., y_dim - 60.),
                 },
                 ..Default::default()
             });
         }).insert(ErrorCard);
+
+        // Retry and Menu Buttons, along the bottom of the card
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(y_dim / 2. + 15.),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.), Val::Px(45.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            for (text, button) in [("Retry", ErrorButton::Retry),
+                                   ("Back to Menu", ErrorButton::Menu)] {
+                parent.spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(170.), Val::Px(45.)),
+                        margin: Rect {
+                            left: Val::Px(10.),
+                            right: Val::Px(10.),
+                            ..Default::default()
+                        },
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: button_colors.none,
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            text,
+                            TextStyle {
+                                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                                font_size: 26.,
+                                color: Color::rgb(1., 1., 1.),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(button);
+            }
+        }).insert(ErrorCard);
     }
 }
 
-// Returns to Menu on a left click after an HTTP error occurs
-fn error_to_menu(errorcard_query: Query<&ErrorCard>,
-                 mouse_button: Res<Input<MouseButton>>,
+// Click handler for the errorcard's buttons. Retrying restarts AppState::Load, which
+// tears down the errorcard and spawns a fresh load task using the same SessionId
+fn error_buttons(mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor, &ErrorButton),
+                                  Pressed>,
+                 button_colors: Res<ButtonMaterials>,
                  mut appstate: ResMut<State<AppState>>,
 ) {
-    if errorcard_query.iter().next().is_some() {
-        if mouse_button.just_pressed(MouseButton::Left) {
-            appstate.set(AppState::Menu).unwrap();
+    for (interaction, pad_click, mut color, button) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                match button {
+                    ErrorButton::Retry => appstate.restart().unwrap(),
+                    ErrorButton::Menu => appstate.set(AppState::Menu).unwrap(),
+                }
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            }
         }
     }
 }
 
+// Marks every question reached this game as played in the cache once the game is
+// over, in a single write left to the IoTaskPool
+fn mark_played(thread_pool: Res<IoTaskPool>, rounds: Res<Rounds>) {
+    let played: Vec<String> = rounds.questions.iter()
+        .take(rounds.round_number)
+        .map(|question| question.text.clone())
+        .collect();
+
+    if !played.is_empty() {
+        thread_pool.spawn(async move {
+            cache::mark_played(&played);
+        }).detach();
+    }
+}
+
 // Tears down loading text and errorcard, if either exists
 fn teardown_loadscreen(loadbar_query: Query<Entity, With<LoadBar>>,
                        errorcard_query: Query<Entity, With<ErrorCard>>,
@@ -296,85 +570,3 @@ fn teardown_loadscreen(loadbar_query: Query<Entity, With<LoadBar>>,
         cmds.entity(errorcard_id).despawn_recursive();
     }
 }
-
-// Async function that handles HTTP queries to OpenTDB
-async fn retrieve_questions(session_id: Option<String>) -> Result<SiteData, ()> {
-    let client = match Client::builder().timeout(Duration::from_secs(20)).build() {
-        Ok(client) => client,
-        Err(_) => return Err(()),
-    };
-    let mut site_data = SiteData::default();
-
-    // Retrieve and set a SessionId if not already set
-    if session_id.is_none() {
-        let session_res = match client.get(
-            "https://opentdb.com/api_token.php?command=request"
-        ).send().await {
-            Ok(response) => response,
-            Err(_) => return Err(()),
-        };
-        
-        let api_res = match session_res.json::<ApiIdResponse>().await {
-            Ok(parsed) => parsed,
-            Err(_) => return Err(()),
-        };
-
-        site_data.session_id = SessionId {
-            id: Some(api_res.token)
-        };
-    } else {
-        site_data.session_id.id = session_id;
-    }
-   
-    // Retrieve trivia questions
-    let res = match client.get(
-        format!("https://opentdb.com/api.php?amount=8&type=multiple&token={}",
-                site_data.session_id.id.as_ref().unwrap())
-    ).send().await {
-        Ok(response) => response,
-        Err(_) => return Err(()),
-    };
-
-    let api_res = match res.json::<ApiQResponse>().await {
-        Ok(parsed) => parsed,
-        Err(_) => return Err(()),
-    };
-    
-    // Format retrieved questions
-    let mut questions = Vec::new();
-    for api_q in api_res.results {
-        // Creates a random ordering of retrieved answers
-        let mut answers = [Answer::default(), Answer::default(), 
-                           Answer::default(), Answer::default()];
-        let t_ind = rand::thread_rng().gen_range(0..4);
-        let mut f_ind = 0;
-        for (i, answer) in answers.iter_mut().enumerate() {
-            if i == t_ind {
-                answer.text = decode_html_entities(&api_q.correct_answer)
-                                      .to_string();
-                answer.truth = true;
-            } else {
-                answer.text = decode_html_entities(&api_q.incorrect_answers[f_ind])
-                                      .to_string();
-                answer.truth = false;
-                f_ind += 1;
-            }
-        }
-
-        questions.push(
-            Question {
-                text: decode_html_entities(&api_q.question).to_string(),
-                answers,
-            }
-        );
-    }
-
-    site_data.rounds = Rounds {
-        round_number: 0,
-        round_max: questions.len(),
-        questions,
-    };
-    
-    Ok(site_data)
-}
-
@@ -4,20 +4,40 @@ use crate::game::ui::UiPlugin;
 use crate::game::answer::CheckPlugin;
 use crate::game::token::TokenPlugin;
 use crate::game::load::LoadPlugin;
+use crate::game::review::ReviewPlugin;
+use crate::game::timer::TimerPlugin;
+use crate::game::survival::SurvivalPlugin;
+use crate::game::daily::DailyPlugin;
+use crate::game::hotseat::HotSeatPlugin;
+use crate::game::bot::BotPlugin;
 
 pub struct GamePlugin;
 
 mod ui;
-mod answer;
-mod token;
-mod load;
+pub mod answer;
+pub mod token;
+pub mod load;
+mod review;
+pub mod timer;
+mod survival;
+pub mod daily;
+pub mod hotseat;
+pub mod bot;
+pub mod source;
+pub mod scoring;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(UiPlugin)
            .add_plugin(CheckPlugin)
            .add_plugin(TokenPlugin)
-           .add_plugin(LoadPlugin);
+           .add_plugin(LoadPlugin)
+           .add_plugin(ReviewPlugin)
+           .add_plugin(TimerPlugin)
+           .add_plugin(SurvivalPlugin)
+           .add_plugin(DailyPlugin)
+           .add_plugin(HotSeatPlugin)
+           .add_plugin(BotPlugin);
     }
 }
 
@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+
+use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::game::answer::WagerHistory;
+use crate::game::load::Rounds;
+
+// Pixels scrolled per line of mouse wheel movement
+const LINE_HEIGHT: f32 = 20.;
+
+pub struct ReviewPlugin;
+
+#[derive(Component)]
+struct ReviewElem;
+#[derive(Component)]
+struct BackButton;
+#[derive(Default, Component)]
+struct ScrollingList {
+    position: f32,
+}
+
+impl Plugin for ReviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+               SystemSet::on_enter(AppState::Review).with_system(spawn_review))
+           .add_system_set(
+               SystemSet::on_update(AppState::Review).with_system(scroll_review)
+                                                     .with_system(back_button))
+           .add_system_set(
+               SystemSet::on_exit(AppState::Review).with_system(teardown_review));
+    }
+}
+
+// Spawns a scrollable list of every question in the last game, along with how the
+// player wagered upon it and what it earned them
+fn spawn_review(asset_server: Res<AssetServer>,
+                button_colors: Res<ButtonMaterials>,
+                windows: Res<Windows>,
+                rounds: Res<Rounds>,
+                history: Res<WagerHistory>,
+                mut cmds: Commands,
+) {
+    let font = asset_server.load("fonts/PublicSans-Medium.ttf");
+    let window = windows.get_primary().unwrap();
+    let text_width = window.width() * 0.9 - 30.;
+
+    cmds.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::ColumnReverse,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        // Title Text
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: Rect::all(Val::Px(15.)),
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "Question Review",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 40.,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+
+        // Review Panel, hiding whatever has been scrolled out of view
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                size: Size::new(Val::Percent(90.), Val::Percent(72.)),
+                overflow: Overflow::Hidden,
+                ..Default::default()
+            },
+            color: Color::rgb(0.92, 0.92, 0.92).into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            // Scrolling List
+            parent.spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::ColumnReverse,
+                    flex_grow: 1.,
+                    max_size: Size::new(Val::Undefined, Val::Undefined),
+                    ..Default::default()
+                },
+                color: Color::NONE.into(),
+                ..Default::default()
+            }).with_children(|parent| {
+                for (i, (question, round)) in rounds.questions.iter()
+                                                    .zip(history.0.iter())
+                                                    .enumerate() {
+                    let mut sections = vec![TextSection {
+                        value: format!("{}. {}\n", i + 1, question.text),
+                        style: TextStyle {
+                            font: font.clone(),
+                            font_size: 22.,
+                            color: Color::BLACK,
+                        },
+                    }];
+
+                    for (j, answer) in question.answers.iter().enumerate() {
+                        let plural = if round.wagers[j] == 1 { "" } else { "s" };
+                        let (note, color) = if j == round.correct {
+                            (" (Correct)", Color::DARK_GREEN)
+                        } else {
+                            ("", Color::BLACK)
+                        };
+
+                        sections.push(TextSection {
+                            value: format!("    {}: {} token{}{}\n", answer.text,
+                                           round.wagers[j], plural, note),
+                            style: TextStyle {
+                                font: font.clone(),
+                                font_size: 18.,
+                                color,
+                            },
+                        });
+                    }
+
+                    sections.push(TextSection {
+                        value: format!("Points: {}", round.points),
+                        style: TextStyle {
+                            font: font.clone(),
+                            font_size: 18.,
+                            color: Color::BLACK,
+                        },
+                    });
+
+                    // Question Entry
+                    parent.spawn_bundle(NodeBundle {
+                        style: Style {
+                            padding: Rect::all(Val::Px(10.)),
+                            ..Default::default()
+                        },
+                        color: Color::NONE.into(),
+                        ..Default::default()
+                    }).with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            style: Style {
+                                max_size: Size::new(Val::Px(text_width), Val::Undefined),
+                                ..Default::default()
+                            },
+                            text: Text {
+                                sections,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        });
+                    });
+                }
+            }).insert(ScrollingList::default());
+        });
+
+        // Back Button
+        parent.spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(150.), Val::Px(45.)),
+                margin: Rect::all(Val::Px(15.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: button_colors.none,
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Back to Menu",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 24.,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        }).insert(BackButton);
+    }).insert(ReviewElem);
+}
+
+// Scrolls the list with the mouse wheel, stopping once either end is in view
+fn scroll_review(mut mouse_wheel: EventReader<MouseWheel>,
+                 mut list_query: Query<(&mut ScrollingList, &mut Style, &Children,
+                                        &Node)>,
+                 entry_query: Query<&Node>,
+) {
+    for wheel in mouse_wheel.iter() {
+        for (mut list, mut style, children, list_node) in list_query.iter_mut() {
+            let entries_height: f32 = children.iter()
+                .filter_map(|&entry| entry_query.get(entry).ok())
+                .map(|entry_node| entry_node.size.y)
+                .sum();
+            let max_scroll = (entries_height - list_node.size.y).max(0.);
+
+            let dy = match wheel.unit {
+                MouseScrollUnit::Line => wheel.y * LINE_HEIGHT,
+                MouseScrollUnit::Pixel => wheel.y,
+            };
+
+            list.position = (list.position + dy).clamp(-max_scroll, 0.);
+            style.position.top = Val::Px(list.position);
+        }
+    }
+}
+
+// Click handler for returning to the menu
+#[allow(clippy::type_complexity)]
+fn back_button(mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                (Pressed, With<BackButton>)>,
+               button_colors: Res<ButtonMaterials>,
+               mut appstate: ResMut<State<AppState>>,
+) {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                appstate.set(AppState::Menu).unwrap();
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            },
+        }
+    }
+}
+
+// Despawns the review screen
+fn teardown_review(review_query: Query<Entity, With<ReviewElem>>, mut cmds: Commands) {
+    for review_id in review_query.iter() {
+        cmds.entity(review_id).despawn_recursive();
+    }
+}
@@ -0,0 +1,161 @@
+// Points awarded per question by the proper scoring rules, whatever the token count
+const PROPER_MAX: i32 = 10;
+
+// A way of turning a player's wagers on a question into points
+pub trait ScoringRule: Send + Sync {
+    // Name shown on the setup screen and scorecard
+    fn name(&self) -> &'static str;
+
+    // Points earned for wagers, the number of tokens placed on each answer in turn,
+    // when the answer at index correct is the true one. Any of the token_count tokens
+    // left unplaced are wasted
+    fn score(&self, wagers: &[usize], correct: usize, token_count: usize) -> i32;
+
+    // Most points a single question can earn when wagering token_count tokens
+    fn max_score(&self, token_count: usize) -> i32;
+}
+
+impl PartialEq for dyn ScoringRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+// Every ScoringRule a game may be played with, in the order they are offered
+pub const SCORING_RULES: [&dyn ScoringRule; 4] = [&Linear, &Logarithmic, &Quadratic,
+                                                  &AllOrNothing];
+
+// One point for every token on the correct answer
+pub struct Linear;
+// Rewards the logarithm of the share of tokens on the correct answer
+pub struct Logarithmic;
+// The Brier score, penalizing the squared error of every answer's share
+pub struct Quadratic;
+// Every point if all tokens are on the correct answer, otherwise none
+pub struct AllOrNothing;
+
+impl ScoringRule for Linear {
+    fn name(&self) -> &'static str {
+        "Linear"
+    }
+
+    fn score(&self, wagers: &[usize], correct: usize, _token_count: usize) -> i32 {
+        wagers[correct] as i32
+    }
+
+    fn max_score(&self, token_count: usize) -> i32 {
+        token_count as i32
+    }
+}
+
+impl ScoringRule for Logarithmic {
+    fn name(&self) -> &'static str {
+        "Logarithmic"
+    }
+
+    // Scaled so that full confidence earns PROPER_MAX and an even spread earns
+    // nothing. An empty correct answer is treated as holding half a token, keeping
+    // the penalty finite
+    fn score(&self, wagers: &[usize], correct: usize, token_count: usize) -> i32 {
+        let total = token_count.max(1) as f32;
+        let share = (wagers[correct] as f32).max(0.5) / total;
+        let answer_count = wagers.len().max(2) as f32;
+
+        (PROPER_MAX as f32 * (1. + share.log(answer_count))).round() as i32
+    }
+
+    fn max_score(&self, _token_count: usize) -> i32 {
+        PROPER_MAX
+    }
+}
+
+impl ScoringRule for Quadratic {
+    fn name(&self) -> &'static str {
+        "Quadratic"
+    }
+
+    // Scaled so that full confidence earns PROPER_MAX and full confidence in a
+    // wrong answer loses as much
+    fn score(&self, wagers: &[usize], correct: usize, token_count: usize) -> i32 {
+        let total = token_count.max(1) as f32;
+        let error: f32 = wagers.iter()
+            .enumerate()
+            .map(|(i, &wager)| {
+                let outcome = if i == correct { 1. } else { 0. };
+                (wager as f32 / total - outcome).powi(2)
+            })
+            .sum();
+
+        (PROPER_MAX as f32 * (1. - error)).round() as i32
+    }
+
+    fn max_score(&self, _token_count: usize) -> i32 {
+        PROPER_MAX
+    }
+}
+
+impl ScoringRule for AllOrNothing {
+    fn name(&self) -> &'static str {
+        "All or Nothing"
+    }
+
+    fn score(&self, wagers: &[usize], correct: usize, token_count: usize) -> i32 {
+        if wagers[correct] == token_count {
+            token_count as i32
+        } else {
+            0
+        }
+    }
+
+    fn max_score(&self, token_count: usize) -> i32 {
+        token_count as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Scores under every rule, in the order of SCORING_RULES
+    fn scores(wagers: &[usize], correct: usize, token_count: usize) -> Vec<i32> {
+        SCORING_RULES.iter()
+            .map(|rule| rule.score(wagers, correct, token_count))
+            .collect()
+    }
+
+    #[test]
+    fn everything_on_the_correct_answer() {
+        assert_eq!(scores(&[0, 5, 0, 0], 1, 5), [5, 10, 10, 5]);
+        for rule in SCORING_RULES {
+            assert_eq!(rule.score(&[0, 5, 0, 0], 1, 5), rule.max_score(5));
+        }
+    }
+
+    #[test]
+    fn everything_on_a_wrong_answer() {
+        assert_eq!(scores(&[5, 0, 0, 0], 1, 5), [0, -7, -10, 0]);
+    }
+
+    #[test]
+    fn even_spread() {
+        let spread = scores(&[2, 2, 2, 2], 3, 8);
+        assert_eq!(spread[0], 2);
+        assert_eq!(spread[1], 0);
+        assert!((-PROPER_MAX..=PROPER_MAX).contains(&spread[2]));
+        assert_eq!(spread[3], 0);
+    }
+
+    #[test]
+    fn unplaced_tokens_are_wasted() {
+        assert_eq!(scores(&[0, 0, 0, 0], 0, 5), [0, -7, 0, 0]);
+        // Even with the rest on the correct answer, All or Nothing earns nothing
+        assert_eq!(scores(&[3, 0, 0, 0], 0, 5), [3, 6, 8, 0]);
+    }
+
+    #[test]
+    fn single_token() {
+        assert_eq!(scores(&[1, 0], 0, 1), [1, 10, 10, 1]);
+        assert_eq!(scores(&[0, 1], 0, 1), [0, 0, -10, 0]);
+        assert_eq!(SCORING_RULES.map(|rule| rule.max_score(1)), [1, 10, 10, 1]);
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use rand::seq::SliceRandom;
+
+use std::sync::Mutex;
+
+use crate::storage::{read_json, unix_time, write_json};
+use crate::game::load::Question;
+use crate::game::source::{QuestionQuery, RawQuestion};
+
+const CACHE_FILE: &str = "question_cache.json";
+// Most questions kept on disk, with the oldest dropped first
+const CACHE_LIMIT: usize = 1000;
+
+// The cache as last read or written, so that only one thread at a time touches the
+// file. Read from disk on first use
+static CACHE: Mutex<Option<Vec<CachedQuestion>>> = Mutex::new(None);
+
+// A previously fetched question, along with where and when it came from
+#[derive(Serialize, Deserialize)]
+struct CachedQuestion {
+    source: String,
+    fetched_at: u64,
+    played: bool,
+    #[serde(flatten)]
+    question: RawQuestion,
+}
+
+// Runs f with the cache locked, reading it from disk if it hasn't been already.
+// Should f change the cache, it must write it out before returning
+fn with_cache<T>(f: impl FnOnce(&mut Vec<CachedQuestion>) -> T) -> T {
+    let mut cache = CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(cache.get_or_insert_with(|| read_json(CACHE_FILE)))
+}
+
+// Saves freshly fetched questions, unplayed, replacing any older copies
+pub fn store(source: &str, questions: &[Question]) {
+    with_cache(|cache| {
+        let fetched_at = unix_time();
+
+        for question in questions {
+            cache.retain(|cached| {
+                cached.source != source || cached.question.question != question.text
+            });
+            cache.push(CachedQuestion {
+                source: source.to_string(),
+                fetched_at,
+                played: false,
+                question: question.to_raw(),
+            });
+        }
+
+        if cache.len() > CACHE_LIMIT {
+            cache.drain(..cache.len() - CACHE_LIMIT);
+        }
+
+        write_json(CACHE_FILE, cache);
+    });
+}
+
+// Draws a random selection of unplayed questions fetched from source which suit the
+// query, provided enough are cached
+pub fn retrieve_unplayed(source: &str, query: &QuestionQuery) -> Option<Vec<Question>> {
+    with_cache(|cache| {
+        let unplayed: Vec<&CachedQuestion> = cache.iter()
+            .filter(|cached| {
+                !cached.played
+                    && cached.source == source
+                    && cached.question.is_playable(query)
+            })
+            .collect();
+
+        if unplayed.len() < query.amount {
+            return None;
+        }
+
+        Some(unplayed.choose_multiple(&mut rand::thread_rng(), query.amount)
+                     .map(|cached| Question::new(&cached.question))
+                     .collect())
+    })
+}
+
+// Records that the questions with the given texts have been played, so they aren't
+// served again as a fallback. This writes the whole cache out, so is best done once
+// a game and off the main thread
+pub fn mark_played(texts: &[String]) {
+    with_cache(|cache| {
+        let mut changed = false;
+        for cached in cache.iter_mut() {
+            if !cached.played && texts.contains(&cached.question.question) {
+                cached.played = true;
+                changed = true;
+            }
+        }
+
+        if changed {
+            write_json(CACHE_FILE, cache);
+        }
+    });
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::game::load::{SiteData, MAX_ANSWERS, MIN_ANSWERS};
+
+pub use crate::game::source::opentdb::{OpenTdb, ResponseCode};
+pub use crate::game::source::pack::{DailySource, PackSource};
+
+pub mod cache;
+mod opentdb;
+mod pack;
+
+// Boxed future returned by a QuestionSource, run on the AsyncComputeTaskPool
+pub type SourceFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+// A category of questions a QuestionSource is able to draw from
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+pub struct Category {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+// A question in the shape OpenTDB serves it, before its answers are shuffled
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RawQuestion {
+    pub category: String,
+    pub difficulty: String,
+    pub question: String,
+    pub correct_answer: String,
+    pub incorrect_answers: Vec<String>,
+}
+
+// Everything a QuestionSource needs to know to produce a round of questions
+#[derive(Clone, Default)]
+pub struct QuestionQuery {
+    pub amount: usize,
+    pub session_id: Option<String>,
+    pub category: Option<Category>,
+    pub difficulty: Option<Difficulty>,
+    pub include_true_false: bool,
+    // Text of the questions already served this game, which mustn't be served again.
+    // A batch continuing a game may come up short, so long as it isn't empty
+    pub exclude: Vec<String>,
+}
+
+// Anything capable of providing trivia questions to the LoadPlugin
+pub trait QuestionSource: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn fetch(&self, query: QuestionQuery) -> SourceFuture<Result<SiteData, LoadError>>;
+    fn categories(&self) -> SourceFuture<Result<Vec<Category>, LoadError>>;
+
+    // Whether questions from this source are worth caching for offline play
+    fn is_remote(&self) -> bool {
+        false
+    }
+}
+
+// Ways in which a QuestionSource can fail to provide questions
+#[derive(Clone, Debug)]
+pub enum LoadError {
+    Timeout,
+    Connection,
+    Status(u16),
+    Decode,
+    Api(ResponseCode),
+    TooFewQuestions {
+        found: usize,
+        wanted: usize,
+    },
+}
+
+// The QuestionSource the LoadPlugin currently retrieves questions from
+#[derive(Clone)]
+pub struct Source(pub Arc<dyn QuestionSource>);
+
+impl RawQuestion {
+    // Whether the question fits on the board and suits the query
+    pub fn is_playable(&self, query: &QuestionQuery) -> bool {
+        let answer_count = self.incorrect_answers.len() + 1;
+
+        (MIN_ANSWERS..=MAX_ANSWERS).contains(&answer_count)
+            && (query.include_true_false || answer_count > 2)
+            && !query.exclude.contains(&self.question)
+            && query.category.as_ref().map_or(true, |c| c.name == self.category)
+            && query.difficulty.map_or(true, |d| {
+                d.as_str().eq_ignore_ascii_case(&self.difficulty)
+            })
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, 
+                                      Difficulty::Hard];
+
+    // Name of the difficulty as used by OpenTDB and question packs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+impl LoadError {
+    // Whether the same request might succeed if made again a little later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            LoadError::Timeout | LoadError::Connection => true,
+            LoadError::Status(status) => *status == 429 || *status >= 500,
+            LoadError::Api(code) => *code == ResponseCode::RateLimit,
+            LoadError::Decode | LoadError::TooFewQuestions { .. } => false,
+        }
+    }
+
+    // What the player can do to get past the error
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            LoadError::Timeout => "The question server may be busy. Please wait a \
+                                   moment and try again.",
+            LoadError::Connection => "Please check your internet connection, or play \
+                                      Offline using a question pack.",
+            LoadError::Status(_) => "The question server may be down. Try again later, \
+                                     or play Offline using a question pack.",
+            LoadError::Decode => "Try again later, or play Offline using a question \
+                                  pack.",
+            LoadError::Api(code) => code.suggestion(),
+            LoadError::TooFewQuestions { .. } => "Try a broader category or difficulty, \
+                                                  or add more questions to your packs.",
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Timeout => write!(f, "Timed out while waiting for your trivia \
+                                            questions."),
+            LoadError::Connection => write!(f, "Couldn't connect to the question \
+                                               server."),
+            LoadError::Status(status) => write!(f, "The question server responded with \
+                                                   HTTP status {}.", status),
+            LoadError::Decode => write!(f, "The question server sent a response that \
+                                           couldn't be understood."),
+            LoadError::Api(code) => write!(f, "{}", code),
+            LoadError::TooFewQuestions { found, wanted } => write!(f, "Only {} of the {} \
+                                                                   questions needed could \
+                                                                   be found.", 
+                                                                   found, wanted),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use serde::Deserialize;
+use html_escape::decode_html_entities;
+use reqwest::Client;
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::game::load::{Question, Rounds, SessionId, SiteData};
+use crate::game::source::{
+    Category,
+    LoadError,
+    QuestionQuery,
+    QuestionSource,
+    RawQuestion,
+    SourceFuture,
+};
+
+// Retrieves questions from the Open Trivia Database
+#[derive(Default)]
+pub struct OpenTdb {
+    // Latest session token, so a retried fetch needn't request another one
+    token: Arc<Mutex<Option<String>>>,
+}
+
+// Status OpenTDB attaches to every response
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(from = "u8")]
+pub enum ResponseCode {
+    Success,
+    NoResults,
+    InvalidParameter,
+    TokenNotFound,
+    TokenEmpty,
+    RateLimit,
+    Unknown(u8),
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct ApiQuestion {
+    category: String,
+    r#type: String,
+    difficulty: String,
+    question: String,
+    correct_answer: String,
+    incorrect_answers: Vec<String>,
+}
+#[derive(Deserialize)]
+struct ApiQResponse {
+    response_code: ResponseCode,
+    results: Vec<ApiQuestion>,
+}
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct ApiIdResponse {
+    response_code: ResponseCode,
+    response_message: String,
+    token: String,
+}
+#[derive(Deserialize)]
+struct ApiResetResponse {
+    response_code: ResponseCode,
+}
+#[derive(Deserialize)]
+struct ApiCategoryResponse {
+    trivia_categories: Vec<Category>,
+}
+
+impl From<u8> for ResponseCode {
+    fn from(code: u8) -> ResponseCode {
+        match code {
+            0 => ResponseCode::Success,
+            1 => ResponseCode::NoResults,
+            2 => ResponseCode::InvalidParameter,
+            3 => ResponseCode::TokenNotFound,
+            4 => ResponseCode::TokenEmpty,
+            5 => ResponseCode::RateLimit,
+            other => ResponseCode::Unknown(other),
+        }
+    }
+}
+
+impl ResponseCode {
+    // What the player can do about OpenTDB responding with this code
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            ResponseCode::NoResults => "Try a broader category or difficulty.",
+            ResponseCode::InvalidParameter => "Try different game settings.",
+            ResponseCode::TokenEmpty => "Try a different category or difficulty.",
+            ResponseCode::RateLimit => "Please wait a few seconds and try again.",
+            ResponseCode::Unknown(_) => "Please try again later.",
+            ResponseCode::Success
+            | ResponseCode::TokenNotFound => "Please try again.",
+        }
+    }
+}
+
+impl fmt::Display for ResponseCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResponseCode::Success => write!(f, "OpenTDB reported success, but sent no \
+                                               usable questions."),
+            ResponseCode::NoResults => write!(f, "OpenTDB doesn't have enough questions \
+                                                 for the chosen category and \
+                                                 difficulty."),
+            ResponseCode::InvalidParameter => write!(f, "OpenTDB didn't understand the \
+                                                        request for questions."),
+            ResponseCode::TokenNotFound => write!(f, "Your OpenTDB session could not be \
+                                                     found, even after requesting a new \
+                                                     one."),
+            ResponseCode::TokenEmpty => write!(f, "You've seen every question OpenTDB has \
+                                                  for these settings, even after \
+                                                  starting a fresh session."),
+            ResponseCode::RateLimit => write!(f, "OpenTDB is receiving too many \
+                                                 requests."),
+            ResponseCode::Unknown(code) => write!(f, "OpenTDB responded with an \
+                                                     unrecognized code ({}).", code),
+        }
+    }
+}
+
+impl From<reqwest::Error> for LoadError {
+    fn from(error: reqwest::Error) -> LoadError {
+        if error.is_timeout() {
+            LoadError::Timeout
+        } else if let Some(status) = error.status() {
+            LoadError::Status(status.as_u16())
+        } else if error.is_decode() {
+            LoadError::Decode
+        } else {
+            LoadError::Connection
+        }
+    }
+}
+
+impl QuestionSource for OpenTdb {
+    fn name(&self) -> &'static str {
+        "OpenTDB"
+    }
+
+    fn fetch(&self, query: QuestionQuery) -> SourceFuture<Result<SiteData, LoadError>> {
+        Box::pin(retrieve_questions(query, self.token.clone()))
+    }
+
+    fn categories(&self) -> SourceFuture<Result<Vec<Category>, LoadError>> {
+        Box::pin(retrieve_categories())
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
+
+// Async function that retrieves the list of categories OpenTDB offers
+async fn retrieve_categories() -> Result<Vec<Category>, LoadError> {
+    let client = Client::builder().timeout(Duration::from_secs(20)).build()?;
+
+    let api_res = client.get("https://opentdb.com/api_category.php")
+                        .send().await?
+                        .error_for_status()?
+                        .json::<ApiCategoryResponse>().await?;
+
+    Ok(api_res.trivia_categories)
+}
+
+// Async function that handles HTTP queries to OpenTDB, recovering once from an
+// exhausted or missing session token
+async fn retrieve_questions(query: QuestionQuery,
+                            token_cache: Arc<Mutex<Option<String>>>,
+) -> Result<SiteData, LoadError> {
+    let client = Client::builder().timeout(Duration::from_secs(20)).build()?;
+
+    // Retrieve a SessionId if not already set
+    let cached = token_cache.lock().unwrap().clone();
+    let mut token = match cached.or_else(|| query.session_id.clone()) {
+        Some(token) => token,
+        None => request_token(&client).await?,
+    };
+    *token_cache.lock().unwrap() = Some(token.clone());
+
+    let mut recovered = false;
+    let api_res = loop {
+        let api_res = request_questions(&client, &query, &token).await?;
+        match api_res.response_code {
+            ResponseCode::Success => break api_res,
+            // Every question has been seen with this token, so start it afresh
+            ResponseCode::TokenEmpty if !recovered => {
+                reset_token(&client, &token).await?;
+            },
+            // Tokens expire after six hours of inactivity, so fetch a new one
+            ResponseCode::TokenNotFound if !recovered => {
+                token = request_token(&client).await?;
+                *token_cache.lock().unwrap() = Some(token.clone());
+            },
+            code => return Err(LoadError::Api(code)),
+        }
+        recovered = true;
+    };
+
+    if api_res.results.len() < query.amount {
+        return Err(LoadError::TooFewQuestions {
+            found: api_res.results.len(),
+            wanted: query.amount,
+        });
+    }
+    
+    // Format retrieved questions
+    let mut questions = Vec::new();
+    for api_q in api_res.results {
+        let incorrect_answers = api_q.incorrect_answers.iter()
+            .map(|answer| decode_html_entities(answer).to_string())
+            .collect();
+
+        questions.push(Question::new(&RawQuestion {
+            category: decode_html_entities(&api_q.category).to_string(),
+            difficulty: api_q.difficulty,
+            question: decode_html_entities(&api_q.question).to_string(),
+            correct_answer: decode_html_entities(&api_q.correct_answer).to_string(),
+            incorrect_answers,
+        }));
+    }
+
+    Ok(SiteData {
+        session_id: SessionId {
+            id: Some(token),
+        },
+        rounds: Rounds {
+            round_number: 0,
+            round_max: questions.len(),
+            questions,
+        },
+    })
+}
+
+// Requests a new session token, which stops OpenTDB from repeating questions
+async fn request_token(client: &Client) -> Result<String, LoadError> {
+    let api_res = client.get("https://opentdb.com/api_token.php?command=request")
+                        .send().await?
+                        .error_for_status()?
+                        .json::<ApiIdResponse>().await?;
+
+    match api_res.response_code {
+        ResponseCode::Success => Ok(api_res.token),
+        code => Err(LoadError::Api(code)),
+    }
+}
+
+// Resets a session token so that all questions may be served again
+async fn reset_token(client: &Client, token: &str) -> Result<(), LoadError> {
+    let url = format!("https://opentdb.com/api_token.php?command=reset&token={}", token);
+    let api_res = client.get(url)
+                        .send().await?
+                        .error_for_status()?
+                        .json::<ApiResetResponse>().await?;
+
+    match api_res.response_code {
+        ResponseCode::Success => Ok(()),
+        code => Err(LoadError::Api(code)),
+    }
+}
+
+// Retrieves trivia questions, narrowed down by any chosen category and difficulty
+async fn request_questions(client: &Client,
+                           query: &QuestionQuery,
+                           token: &str,
+) -> Result<ApiQResponse, LoadError> {
+    let mut url = format!("https://opentdb.com/api.php?amount={}&token={}",
+                          query.amount, token);
+    if !query.include_true_false {
+        url.push_str("&type=multiple");
+    }
+    if let Some(category) = &query.category {
+        url.push_str(&format!("&category={}", category.id));
+    }
+    if let Some(difficulty) = query.difficulty {
+        url.push_str(&format!("&difficulty={}", difficulty.as_str()));
+    }
+
+    let api_res = client.get(url)
+                        .send().await?
+                        .error_for_status()?
+                        .json::<ApiQResponse>().await?;
+
+    Ok(api_res)
+}
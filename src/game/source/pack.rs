@@ -0,0 +1,239 @@
+use bevy::log::warn;
+use serde::Deserialize;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::storage::{assets_dir, data_dir};
+use crate::game::daily::DailyDate;
+use crate::game::load::{Question, Rounds, SessionId, SiteData};
+use crate::game::source::{
+    Category,
+    LoadError,
+    QuestionQuery,
+    QuestionSource,
+    RawQuestion,
+    SourceFuture,
+};
+
+// Retrieves questions from hand-authored pack files on the local disk
+#[derive(Clone)]
+pub struct PackSource {
+    dirs: Vec<PathBuf>,
+}
+
+// Draws the same questions, in the same order with the same answer shuffle, for
+// everyone playing on the same date. Questions come only from the packs shipped with
+// the game, as anyone's own packs or cache would make their set differ
+pub struct DailySource {
+    pub date: DailyDate,
+    packs: PackSource,
+}
+
+// Questions mirror the format used by OpenTDB so packs are easy to author
+#[derive(Deserialize)]
+struct PackFile {
+    questions: Vec<RawQuestion>,
+}
+
+impl Default for PackSource {
+    // Looks for packs in assets/packs and in the user's data directory
+    fn default() -> PackSource {
+        let mut dirs = vec![assets_dir().join("packs")];
+        if let Some(user_dir) = data_dir() {
+            dirs.push(user_dir.join("packs"));
+        }
+
+        PackSource { dirs }
+    }
+}
+
+impl PackSource {
+    // Looks for packs only in assets/packs, among the files shipped with the game
+    pub fn shipped() -> PackSource {
+        PackSource {
+            dirs: vec![assets_dir().join("packs")],
+        }
+    }
+}
+
+impl DailySource {
+    // Seeded from today's date in UTC
+    pub fn today() -> DailySource {
+        DailySource {
+            date: DailyDate::today(),
+            packs: PackSource::shipped(),
+        }
+    }
+}
+
+impl QuestionSource for PackSource {
+    fn name(&self) -> &'static str {
+        "Local Packs"
+    }
+
+    fn fetch(&self, query: QuestionQuery) -> SourceFuture<Result<SiteData, LoadError>> {
+        let dirs = self.dirs.clone();
+        Box::pin(async move {
+            retrieve_questions(&dirs, query)
+        })
+    }
+
+    fn categories(&self) -> SourceFuture<Result<Vec<Category>, LoadError>> {
+        let dirs = self.dirs.clone();
+        Box::pin(async move {
+            Ok(retrieve_categories(&dirs))
+        })
+    }
+}
+
+impl QuestionSource for DailySource {
+    fn name(&self) -> &'static str {
+        "Daily Challenge"
+    }
+
+    fn fetch(&self, query: QuestionQuery) -> SourceFuture<Result<SiteData, LoadError>> {
+        let dirs = self.packs.dirs.clone();
+        let seed = self.date.seed();
+        Box::pin(async move {
+            retrieve_daily(&dirs, seed, query)
+        })
+    }
+
+    // The daily set is the same whatever the category, so none are offered
+    fn categories(&self) -> SourceFuture<Result<Vec<Category>, LoadError>> {
+        Box::pin(async move {
+            Ok(Vec::new())
+        })
+    }
+}
+
+// Every category named by at least one pack question, in alphabetical order
+fn retrieve_categories(dirs: &[PathBuf]) -> Vec<Category> {
+    let mut names: Vec<String> = read_dirs(dirs).into_iter()
+        .map(|pack_q| pack_q.category)
+        .collect();
+    names.sort();
+    names.dedup();
+
+    names.into_iter()
+        .enumerate()
+        .map(|(i, name)| Category { id: i as u32, name })
+        .collect()
+}
+
+// Draws a random selection of questions from every pack found in dirs, leaving out
+// any the query excludes
+fn retrieve_questions(dirs: &[PathBuf],
+                      query: QuestionQuery,
+) -> Result<SiteData, LoadError> {
+    let pack_qs = playable(read_dirs(dirs), &query);
+
+    // A game being continued takes whatever is left
+    let continuing = !query.exclude.is_empty();
+    if pack_qs.is_empty() || (pack_qs.len() < query.amount && !continuing) {
+        return Err(LoadError::TooFewQuestions {
+            found: pack_qs.len(),
+            wanted: query.amount,
+        });
+    }
+
+    let questions: Vec<Question> = pack_qs
+        .choose_multiple(&mut rand::thread_rng(), query.amount)
+        .map(Question::new)
+        .collect();
+
+    Ok(SiteData {
+        session_id: SessionId {
+            id: query.session_id,
+        },
+        rounds: Rounds {
+            round_number: 0,
+            round_max: questions.len(),
+            questions,
+        },
+    })
+}
+
+// Draws a selection from the packs found in dirs seeded by seed, so the same seed
+// always draws the same questions with the same answer shuffle
+fn retrieve_daily(dirs: &[PathBuf],
+                  seed: u64,
+                  query: QuestionQuery,
+) -> Result<SiteData, LoadError> {
+    let pack_qs = playable(read_dirs(dirs), &query);
+    if pack_qs.len() < query.amount {
+        return Err(LoadError::TooFewQuestions {
+            found: pack_qs.len(),
+            wanted: query.amount,
+        });
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let chosen: Vec<&RawQuestion> = pack_qs.choose_multiple(&mut rng, query.amount)
+        .collect();
+    let questions: Vec<Question> = chosen.into_iter()
+        .map(|raw| Question::with_rng(raw, &mut rng))
+        .collect();
+
+    Ok(SiteData {
+        session_id: SessionId {
+            id: query.session_id,
+        },
+        rounds: Rounds {
+            round_number: 0,
+            round_max: questions.len(),
+            questions,
+        },
+    })
+}
+
+// The questions among pack_qs which suit the query, each only once however many packs
+// hold it. They're sorted by text, so the order they were read from disk in never
+// matters
+fn playable(mut pack_qs: Vec<RawQuestion>, query: &QuestionQuery) -> Vec<RawQuestion> {
+    pack_qs.retain(|pack_q| pack_q.is_playable(query));
+    pack_qs.sort_by(|a, b| a.question.cmp(&b.question));
+    pack_qs.dedup_by(|a, b| a.question == b.question);
+
+    pack_qs
+}
+
+// Every question in every pack found in dirs
+fn read_dirs(dirs: &[PathBuf]) -> Vec<RawQuestion> {
+    dirs.iter().flat_map(|dir| read_packs(dir)).collect()
+}
+
+// Parses every .json and .toml pack in a directory, skipping those that are malformed
+fn read_packs(dir: &Path) -> Vec<RawQuestion> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut pack_qs = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+
+        let parsed = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str::<PackFile>(&contents)
+                                .map_err(|e| e.to_string()),
+            Some("toml") => toml::from_str::<PackFile>(&contents)
+                                .map_err(|e| e.to_string()),
+            _ => continue,
+        };
+
+        match parsed {
+            Ok(pack) => pack_qs.extend(pack.questions),
+            Err(e) => warn!("Skipping question pack {}: {}", path.display(), e),
+        }
+    }
+
+    pack_qs
+}
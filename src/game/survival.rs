@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicU32;
+
+use crate::AppState;
+use crate::setup::{GameMode, GameSettings};
+use crate::game::answer::WagerHistory;
+use crate::game::load::{self, Question, Rounds, RetryPolicy, SessionId, SiteData};
+use crate::game::source::{LoadError, Source};
+
+// Lives a survival game begins with
+pub const STARTING_LIVES: usize = 3;
+// Questions left unplayed at which the next batch is fetched
+const PREFETCH_AT: usize = 3;
+
+pub struct SurvivalPlugin;
+
+// Lives left in a survival game, along with whether the Source has run dry
+#[derive(Default)]
+pub struct Lives {
+    pub remaining: usize,
+    exhausted: bool,
+}
+
+// Questions fetched in the background, waiting to join Rounds as the next round
+// begins so that the current round is left undisturbed
+#[derive(Default)]
+pub struct PendingQuestions(pub Vec<Question>);
+
+#[derive(Component)]
+pub struct LivesCount;
+// The next batch of questions, fetched while the current one is played
+#[derive(Component)]
+struct Prefetch(Task<Result<SiteData, LoadError>>);
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lives>()
+           .init_resource::<PendingQuestions>()
+           .add_system_set(
+               SystemSet::on_enter(AppState::Game).with_system(reset_lives))
+           .add_system_set(
+               SystemSet::on_update(AppState::Game).with_system(lose_lives)
+                                                   .with_system(spawn_prefetch)
+                                                   .with_system(append_prefetch)
+                                                   .with_system(update_lives))
+           .add_system_set(
+               SystemSet::on_exit(AppState::Game).with_system(teardown_prefetch));
+    }
+}
+
+// The fewest tokens which must land on the correct answer to keep a life
+pub fn survival_threshold(token_count: usize) -> usize {
+    token_count.div_ceil(2)
+}
+
+// Whether a survival game has reached its last question with lives to spare while
+// the next batch is still being fetched, so the next round must wait on it
+pub fn awaiting_questions(rounds: &Rounds,
+                          pending: &PendingQuestions,
+                          lives: &Lives,
+                          settings: &GameSettings,
+) -> bool {
+    settings.mode == GameMode::Survival && lives.remaining > 0 && !lives.exhausted
+        && pending.0.is_empty() && rounds.round_number + 1 >= rounds.questions.len()
+}
+
+fn reset_lives(mut lives: ResMut<Lives>, mut pending: ResMut<PendingQuestions>) {
+    lives.remaining = STARTING_LIVES;
+    lives.exhausted = false;
+    pending.0.clear();
+}
+
+// Takes a life for every question where too few tokens landed on the correct answer.
+// The game ends once the round which took the last is over
+fn lose_lives(mut lives: ResMut<Lives>,
+              history: Res<WagerHistory>,
+              settings: Res<GameSettings>,
+) {
+    if settings.mode != GameMode::Survival || !history.is_changed() {
+        return;
+    }
+
+    if let Some(round) = history.0.last() {
+        if round.wagers[round.correct] < survival_threshold(settings.token_count) {
+            lives.remaining = lives.remaining.saturating_sub(1);
+        }
+    }
+}
+
+// Starts fetching the next batch of questions once the current one runs low
+#[allow(clippy::too_many_arguments)]
+fn spawn_prefetch(thread_pool: Res<AsyncComputeTaskPool>,
+                  prefetch_query: Query<&Prefetch>,
+                  pending: Res<PendingQuestions>,
+                  lives: Res<Lives>,
+                  rounds: Res<Rounds>,
+                  session_id: Res<SessionId>,
+                  source: Res<Source>,
+                  settings: Res<GameSettings>,
+                  retry_policy: Res<RetryPolicy>,
+                  mut cmds: Commands,
+) {
+    let running_low = rounds.questions.len() - rounds.round_number <= PREFETCH_AT;
+    if settings.mode != GameMode::Survival || lives.remaining == 0 || lives.exhausted
+       || !running_low || !pending.0.is_empty() || !prefetch_query.is_empty() {
+        return;
+    }
+
+    // Never serve a question twice in the same run
+    let mut query = load::question_query(&settings, &session_id);
+    query.exclude = rounds.questions.iter().map(|question| question.text.clone()).collect();
+
+    let batch_get = load::spawn_fetch(&thread_pool,
+                                      source.0.clone(),
+                                      query,
+                                      retry_policy.clone(),
+                                      Arc::new(AtomicU32::new(1)));
+
+    cmds.spawn().insert(Prefetch(batch_get));
+}
+
+// Holds a fetched batch until the next round begins, so long as the game is still
+// going
+fn append_prefetch(mut prefetch_query: Query<(Entity, &mut Prefetch)>,
+                   mut pending: ResMut<PendingQuestions>,
+                   mut lives: ResMut<Lives>,
+                   mut session_id: ResMut<SessionId>,
+                   mut cmds: Commands,
+) {
+    for (entity, mut prefetch) in prefetch_query.iter_mut() {
+        if let Some(site_res) = future::block_on(future::poll_once(&mut prefetch.0)) {
+            match site_res {
+                // A batch with nothing in it means the Source has nothing more to give
+                Ok(site_data) if site_data.rounds.questions.is_empty() => {
+                    lives.exhausted = true;
+                },
+                Ok(site_data) if lives.remaining > 0 => {
+                    *session_id = site_data.session_id;
+                    pending.0.extend(site_data.rounds.questions);
+                },
+                Ok(_) => (),
+                // Play out whatever questions are left
+                Err(error) => {
+                    warn!("Couldn't fetch more survival questions: {}", error);
+                    lives.exhausted = true;
+                },
+            }
+
+            cmds.entity(entity).despawn();
+        }
+    }
+}
+
+// Keeps the LivesCount text in sync with Lives
+fn update_lives(mut lives_query: Query<(&mut Text, ChangeTrackers<LivesCount>)>,
+                lives: Res<Lives>,
+) {
+    for (mut text, tracker) in lives_query.iter_mut() {
+        if lives.is_changed() || tracker.is_added() {
+            text.sections[0].value = format!("Lives: {}", lives.remaining);
+        }
+    }
+}
+
+// Drops any batch still being fetched when the game ends
+fn teardown_prefetch(prefetch_query: Query<Entity, With<Prefetch>>, mut cmds: Commands) {
+    for prefetch_id in prefetch_query.iter() {
+        cmds.entity(prefetch_id).despawn();
+    }
+}
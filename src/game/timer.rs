@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use crate::AppState;
+use crate::setup::GameSettings;
+use crate::game::answer::{self, SubmitPressed};
+use crate::game::load::Rounds;
+use crate::game::hotseat::TurnStarted;
+
+const FULL_COLOR: Color = Color::rgb(0.2, 0.7, 0.3);
+const EMPTY_COLOR: Color = Color::rgb(0.85, 0.15, 0.15);
+
+pub struct TimerPlugin;
+
+// Counts down the time left on the current question in timed mode. Stops once the
+// question is submitted, holding however much time was left
+#[derive(Default)]
+pub struct QuestionTimer {
+    timer: Option<Timer>,
+    running: bool,
+}
+
+#[derive(Component)]
+pub struct CountdownBar;
+
+impl Plugin for TimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuestionTimer>()
+           .add_system_set(
+               SystemSet::on_enter(AppState::Game).with_system(reset_timer))
+           .add_system_set(
+               SystemSet::on_update(AppState::Game).with_system(restart_timer)
+                                                   .with_system(tick_timer)
+                                                   .with_system(update_countdown));
+    }
+}
+
+impl QuestionTimer {
+    // Share of the time limit left, which is always all of it when untimed
+    pub fn remaining(&self) -> f32 {
+        self.timer.as_ref().map_or(1., |timer| 1. - timer.percent())
+    }
+}
+
+// Sets the timer up for a new game, if it is to be timed at all
+fn reset_timer(mut question_timer: ResMut<QuestionTimer>, settings: Res<GameSettings>) {
+    question_timer.timer = settings.time_limit
+        .map(|seconds| Timer::from_seconds(seconds as f32, false));
+    question_timer.running = false;
+}
+
+// Starts the clock afresh at the beginning of every question, and of every turn in
+// hot seat
+fn restart_timer(mut question_timer: ResMut<QuestionTimer>,
+                 mut turn_started: EventReader<TurnStarted>,
+                 rounds: Res<Rounds>,
+) {
+    let turn_started = turn_started.iter().last().is_some();
+    if (rounds.is_changed() || turn_started) && rounds.round_number < rounds.round_max {
+        if let Some(timer) = question_timer.timer.as_mut() {
+            timer.reset();
+            question_timer.running = true;
+        }
+    }
+}
+
+// Runs down the clock, submitting whatever tokens are placed once time expires
+fn tick_timer(mut question_timer: ResMut<QuestionTimer>,
+              mut submit_pressed: ParamSet<(EventReader<SubmitPressed>,
+                                            EventWriter<SubmitPressed>)>,
+              mut windows: ResMut<Windows>,
+              time: Res<Time>,
+) {
+    if submit_pressed.p0().iter().last().is_some() {
+        question_timer.running = false;
+    }
+    if !question_timer.running {
+        return;
+    }
+
+    let expired = question_timer.timer.as_mut()
+        .is_some_and(|timer| timer.tick(time.delta()).just_finished());
+    if expired {
+        question_timer.running = false;
+
+        answer::press_submit(&mut windows, &mut submit_pressed.p1());
+    }
+}
+
+// Shrinks the CountdownBar and shades it from green to red as time runs out
+fn update_countdown(mut bar_query: Query<(&mut Style, &mut UiColor), With<CountdownBar>>,
+                    question_timer: Res<QuestionTimer>,
+) {
+    if !question_timer.is_changed() {
+        return;
+    }
+
+    let remaining = question_timer.remaining();
+    let [full_r, full_g, full_b, _] = FULL_COLOR.as_rgba_f32();
+    let [empty_r, empty_g, empty_b, _] = EMPTY_COLOR.as_rgba_f32();
+
+    for (mut style, mut color) in bar_query.iter_mut() {
+        style.size.height = Val::Percent(100. * remaining);
+        *color = Color::rgb(empty_r + (full_r - empty_r) * remaining,
+                            empty_g + (full_g - empty_g) * remaining,
+                            empty_b + (full_b - empty_b) * remaining).into();
+    }
+}
@@ -1,8 +1,13 @@
+// Bevy 0.7's Bundle derive forgets each field after moving it, which clippy flags
+#![allow(clippy::forget_non_drop)]
+
 use bevy::prelude::*;
+use bevy::input::touch::Touch;
 
 use crate::AppState;
 use crate::game::answer::{AnswerBlock, AnswerColor};
 use crate::game::load::Rounds;
+use crate::game::hotseat::{PassScreen, TurnPassed};
 
 // Hardcoded for now for predetermined screen size
 const OFFSET_X: f32 = -400.;
@@ -23,6 +28,9 @@ struct TokenSprite;
 struct Draggable;
 #[derive(Component)]
 struct Dragged;
+// A token being dragged by the finger with the given touch id
+#[derive(Component)]
+struct TouchDragged(u64);
 #[derive(Default, Component)]
 pub struct SideLength {
     pub x_len: f32,
@@ -31,6 +39,14 @@ pub struct SideLength {
 #[derive(Component)]
 pub struct On(pub Entity);
 
+// Moves a token without the mouse, naming answers by their index
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenAction {
+    Place(usize),
+    Remove(usize),
+    Clear,
+}
+
 #[derive(Default, Bundle)]
 struct TokenBundle {
     token: Token,
@@ -42,11 +58,16 @@ struct TokenBundle {
 
 impl Plugin for TokenPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_to_stage(CoreStage::Last, spawn_tokens)
+        app.add_event::<TokenAction>()
+           .add_system_to_stage(CoreStage::Last, spawn_tokens)
            .add_system_set(
                SystemSet::on_update(AppState::Game).with_system(up_draggable)
                                                    .with_system(down_draggable)
                                                    .with_system(drag_token)
+                                                   .with_system(touch_pick_up)
+                                                   .with_system(touch_drag)
+                                                   .with_system(touch_put_down)
+                                                   .with_system(apply_token_actions)
                                                    .with_system(reset_tokens))
            .add_system_set(
                SystemSet::on_exit(AppState::Game).with_system(teardown_tokens));
@@ -88,14 +109,26 @@ fn spawn_tokens(mut cmds: Commands, asset_server: Res<AssetServer>,
 }
 
 // Sets a Draggable element to Dragged if it was clicked
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn up_draggable(btn_press: Res<Input<MouseButton>>,
                 mut draggable_query: Query<(Entity, &SideLength, &mut Transform), 
-                                        With<Draggable>>,
+                                        (With<Draggable>, Without<TouchDragged>)>,
                 dragged_query: Query<With<Dragged>>,
+                pass_query: Query<With<PassScreen>>,
                 mut sprite_query: Query<(&mut Sprite, &Parent), With<TokenSprite>>,
                 mut cmds: Commands,
                 windows: Res<Windows>,
+                touches: Res<Touches>,
 ) {
+    // Tokens stay put while the board is being passed to the next player
+    if !pass_query.is_empty() {
+        return;
+    }
+    // Touches are also reported as mouse clicks, which would lift a second token
+    if touches.iter().next().is_some() {
+        return;
+    }
+
     if btn_press.just_pressed(MouseButton::Left) && dragged_query.iter().len() == 0 {
         // Find current cursor coords
         let window = windows.get_primary().unwrap();
@@ -108,16 +141,8 @@ fn up_draggable(btn_press: Res<Input<MouseButton>>,
         // Check to see if click was on any Draggable
         for (entity_id, bounds, mut drag_t) in draggable_query.iter_mut() {
             if in_bounds(&cursor_coords, bounds, &drag_t.translation) {
-                cmds.entity(entity_id).remove::<On>();
+                lift_token(entity_id, &mut drag_t, &mut sprite_query, &mut cmds);
                 cmds.entity(entity_id).insert(Dragged);
-                drag_t.translation.z += 1.; // So Dragged above other Draggables
-
-                // Change color back to default
-                for (mut sprite, parent) in sprite_query.iter_mut() {
-                    if parent.0 == entity_id {
-                        sprite.color = DEFAULT_COLOR;
-                    }
-                }
 
                 break; // To ensure only one token dragged at a time
             }
@@ -138,28 +163,136 @@ fn down_draggable(btn_press: Res<Input<MouseButton>>,
         for (entity_id, dragged_gt, mut dragged_t) in dragged_query.iter_mut() {
             // Stop the entity being dragged
             cmds.entity(entity_id).remove::<Dragged>();
-            dragged_t.translation.z -= 1.;
-
-            let down_pos = Vec2::new(dragged_gt.translation.x,
-                                     dragged_gt.translation.y);
-            
-            // Check if it was put down in an Answer
-            for (ans_entity, ans_gt, ans_sides, ans_color) in answer_query.iter() {
-                if in_bounds(&down_pos, ans_sides, &ans_gt.translation) {
-                    cmds.entity(entity_id).insert(On(ans_entity));
-
-                    // Change to answers color
-                    for (mut sprite, parent) in sprite_query.iter_mut() {
-                        if parent.0 == entity_id {
-                            sprite.color = ans_color.0; 
-                        }
-                    }
+            put_down_token(entity_id, dragged_gt, &mut dragged_t, &answer_query,
+                           &mut sprite_query, &mut cmds);
+        }
+    }
+}
+
+// Raises a token above the others as it is picked up, taking it off any answer
+fn lift_token(token_id: Entity,
+              token_t: &mut Transform,
+              sprite_query: &mut Query<(&mut Sprite, &Parent), With<TokenSprite>>,
+              cmds: &mut Commands,
+) {
+    cmds.entity(token_id).remove::<On>();
+    token_t.translation.z += 1.; // So Dragged above other Draggables
+
+    // Change color back to default
+    for (mut sprite, parent) in sprite_query.iter_mut() {
+        if parent.0 == token_id {
+            sprite.color = DEFAULT_COLOR;
+        }
+    }
+}
+
+// Lowers a token as it is let go, placing it On whichever answer it landed in
+fn put_down_token(token_id: Entity,
+                  token_gt: &GlobalTransform,
+                  token_t: &mut Transform,
+                  answer_query: &Query<(Entity, &GlobalTransform, &SideLength,
+                                        &AnswerColor), With<AnswerBlock>>,
+                  sprite_query: &mut Query<(&mut Sprite, &Parent), With<TokenSprite>>,
+                  cmds: &mut Commands,
+) {
+    token_t.translation.z -= 1.;
+
+    let down_pos = Vec2::new(token_gt.translation.x, token_gt.translation.y);
+
+    // Check if it was put down in an Answer
+    for (ans_entity, ans_gt, ans_sides, ans_color) in answer_query.iter() {
+        if in_bounds(&down_pos, ans_sides, &ans_gt.translation) {
+            cmds.entity(token_id).insert(On(ans_entity));
+
+            // Change to answers color
+            for (mut sprite, parent) in sprite_query.iter_mut() {
+                if parent.0 == token_id {
+                    sprite.color = ans_color.0; 
                 }
             }
         }
     }
 }
 
+// Where a touch lies in the same coordinates as the cursor. Bevy reports touches
+// from the top of the window on desktops, but the cursor from the bottom
+fn touch_position(touch: &Touch, window: &Window) -> Vec2 {
+    let position = touch.position();
+    if cfg!(any(target_os = "android", target_os = "ios")) {
+        position
+    } else {
+        Vec2::new(position.x, window.height() - position.y)
+    }
+}
+
+// Picks up the token under each new touch, so that every finger drags its own token
+#[allow(clippy::type_complexity)]
+fn touch_pick_up(touches: Res<Touches>,
+                 mut draggable_query: Query<(Entity, &SideLength, &mut Transform),
+                     (With<Draggable>, Without<Dragged>, Without<TouchDragged>)>,
+                 pass_query: Query<With<PassScreen>>,
+                 mut sprite_query: Query<(&mut Sprite, &Parent), With<TokenSprite>>,
+                 windows: Res<Windows>,
+                 mut cmds: Commands,
+) {
+    // Tokens stay put while the board is being passed to the next player
+    if !pass_query.is_empty() {
+        return;
+    }
+
+    let window = windows.get_primary().unwrap();
+    let mut picked = Vec::new();
+    for touch in touches.iter_just_pressed() {
+        let touch_coords = touch_position(touch, window) + Vec2::new(OFFSET_X, OFFSET_Y);
+
+        for (entity_id, bounds, mut drag_t) in draggable_query.iter_mut() {
+            if !picked.contains(&entity_id)
+               && in_bounds(&touch_coords, bounds, &drag_t.translation) {
+                lift_token(entity_id, &mut drag_t, &mut sprite_query, &mut cmds);
+                cmds.entity(entity_id).insert(TouchDragged(touch.id()));
+                picked.push(entity_id);
+
+                break; // One token to a finger
+            }
+        }
+    }
+}
+
+// Moves each touch-dragged token along with its finger
+fn touch_drag(touches: Res<Touches>,
+              mut dragged_query: Query<(&TouchDragged, &mut Transform)>,
+              windows: Res<Windows>,
+) {
+    let window = windows.get_primary().unwrap();
+    for (touch_dragged, mut drag_t) in dragged_query.iter_mut() {
+        if let Some(touch) = touches.get_pressed(touch_dragged.0) {
+            let new_t = touch_position(touch, window) + Vec2::new(OFFSET_X, OFFSET_Y);
+            drag_t.translation.x = new_t.x;
+            drag_t.translation.y = new_t.y;
+        }
+    }
+}
+
+// Puts down a touch-dragged token once its finger lifts, or the touch is cancelled
+fn touch_put_down(touches: Res<Touches>,
+                  mut dragged_query: Query<(Entity, &TouchDragged, &GlobalTransform,
+                                            &mut Transform)>,
+                  answer_query: Query<(Entity, &GlobalTransform, &SideLength,
+                      &AnswerColor), With<AnswerBlock>>,
+                  mut sprite_query: Query<(&mut Sprite, &Parent), With<TokenSprite>>,
+                  mut cmds: Commands,
+) {
+    for (entity_id, touch_dragged, dragged_gt, mut dragged_t) in dragged_query.iter_mut() {
+        let id = touch_dragged.0;
+        if touches.just_released(id) || touches.just_cancelled(id)
+           || touches.get_pressed(id).is_none() {
+            cmds.entity(entity_id).remove::<TouchDragged>();
+            put_down_token(entity_id, dragged_gt, &mut dragged_t, &answer_query,
+                           &mut sprite_query, &mut cmds);
+        }
+    }
+}
+
 // Moves a token being dragged by the cursor by setting its Translation to
 // match that of the cursor
 fn drag_token(mut cursor_move: EventReader<CursorMoved>,
@@ -174,15 +307,119 @@ fn drag_token(mut cursor_move: EventReader<CursorMoved>,
     }
 }
 
+// Carries out TokenActions, fanning the tokens on each answer out along it so that
+// every one stays visible
+#[allow(clippy::type_complexity)]
+fn apply_token_actions(mut actions: EventReader<TokenAction>,
+                       mut token_query: Query<(Entity, &SideLength, &mut Transform,
+                                               &StartSlot, Option<&On>),
+                                              (With<Token>, Without<Dragged>,
+                                               Without<TouchDragged>)>,
+                       answer_query: Query<(Entity, &AnswerBlock, &GlobalTransform,
+                                            &SideLength, &AnswerColor)>,
+                       slot_query: Query<&GlobalTransform, With<TokenSlot>>,
+                       mut sprite_query: Query<(&mut Sprite, &Parent), With<TokenSprite>>,
+                       mut cmds: Commands,
+) {
+    // Where every token will sit, kept up to date as each action is applied
+    let mut placements: Vec<(Entity, Option<Entity>)> = token_query.iter()
+        .map(|(token_id, _, _, _, on)| (token_id, on.map(|on| on.0)))
+        .collect();
+    let mut touched = Vec::new();
+
+    for action in actions.iter() {
+        let block_of = |index: usize| answer_query.iter()
+            .find(|(_, answer_block, ..)| answer_block.0 == index)
+            .map(|(block_id, ..)| block_id);
+
+        match *action {
+            TokenAction::Place(index) => if let Some(block_id) = block_of(index) {
+                if let Some(placement) = placements.iter_mut().find(|(_, on)| on.is_none()) {
+                    placement.1 = Some(block_id);
+                    touched.push(block_id);
+                }
+            },
+            TokenAction::Remove(index) => if let Some(block_id) = block_of(index) {
+                if let Some(placement) = placements.iter_mut().rev()
+                                                   .find(|(_, on)| *on == Some(block_id)) {
+                    placement.1 = None;
+                    touched.push(block_id);
+                }
+            },
+            TokenAction::Clear => {
+                for placement in placements.iter_mut() {
+                    touched.extend(placement.1.take());
+                }
+            },
+        }
+    }
+
+    for (block_id, _, block_gt, block_sides, block_color) in answer_query.iter() {
+        if !touched.contains(&block_id) {
+            continue;
+        }
+
+        let on_block: Vec<Entity> = placements.iter()
+            .filter(|(_, on)| *on == Some(block_id))
+            .map(|(token_id, _)| *token_id)
+            .collect();
+        for (k, &token_id) in on_block.iter().enumerate() {
+            if let Ok((_, token_sides, mut token_t, _, _)) = token_query.get_mut(token_id) {
+                let step = if on_block.len() > 1 {
+                    f32::min(token_sides.x_len, (block_sides.x_len - token_sides.x_len)
+                                                / (on_block.len() - 1) as f32)
+                } else {
+                    0.
+                };
+                token_t.translation.x = block_gt.translation.x - block_sides.x_len / 2.
+                    + token_sides.x_len / 2. + step * k as f32;
+                token_t.translation.y = block_gt.translation.y;
+                token_t.translation.z = 5. + 0.01 * k as f32;
+            }
+
+            cmds.entity(token_id).insert(On(block_id));
+            for (mut sprite, parent) in sprite_query.iter_mut() {
+                if parent.0 == token_id {
+                    sprite.color = block_color.0;
+                }
+            }
+        }
+    }
+
+    // Tokens taken off an answer go back to where they started
+    for (token_id, _, mut token_t, token_slot, on) in token_query.iter_mut() {
+        let lifted = on.is_some() && placements.iter()
+            .any(|(placed_id, placed_on)| *placed_id == token_id && placed_on.is_none());
+        if !lifted {
+            continue;
+        }
+
+        if let Ok(slot_gt) = slot_query.get(token_slot.0) {
+            token_t.translation.x = slot_gt.translation.x + OFFSET_X;
+            token_t.translation.y = slot_gt.translation.y + OFFSET_Y;
+            token_t.translation.z = 5.;
+        }
+        cmds.entity(token_id).remove::<On>();
+        for (mut sprite, parent) in sprite_query.iter_mut() {
+            if parent.0 == token_id {
+                sprite.color = DEFAULT_COLOR;
+            }
+        }
+    }
+}
+
 // Resets tokens to their original state when the next round is begun
 fn reset_tokens(mut token_query: Query<(Entity, &mut Transform, &StartSlot),
                     With<Token>>,
                 mut token_sprites: Query<&mut Sprite, With<TokenSprite>>,
                 slot_query: Query<&GlobalTransform, With<TokenSlot>>,
                 rounds: Res<Rounds>,
+                mut turn_passed: EventReader<TurnPassed>,
                 mut cmds: Commands,
 ) {
-    if rounds.is_changed() {
+    // Hot seat clears the board for each player in turn, as well as each question
+    let turn_passed = turn_passed.iter().last().is_some();
+    if rounds.is_changed() || turn_passed {
         // Reset token positions to original slot
         for (token_id, mut token_t, token_slot) in token_query.iter_mut() {
             if let Ok(slot_gt) = slot_query.get(token_slot.0) {
@@ -191,6 +428,8 @@ fn reset_tokens(mut token_query: Query<(Entity, &mut Transform, &StartSlot),
                 token_t.translation.z = 5.;
             }
             cmds.entity(token_id).remove::<On>();
+            cmds.entity(token_id).remove::<Dragged>();
+            cmds.entity(token_id).remove::<TouchDragged>();
         }
 
         // Reset all tokens to default color
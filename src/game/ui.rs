@@ -1,25 +1,47 @@
 use bevy::prelude::*;
 use bevy::text::Text2dBounds;
 
-use crate::AppState;
+use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::setup::{GameMode, GameSettings};
 use crate::game::answer::{
+    question_count_text,
     QuestionSlot,
-    AnswerSlot,
+    AnswerGrid,
     SubmitButton,
 };
 use crate::game::token::TokenSlot;
+use crate::game::timer::CountdownBar;
+use crate::game::survival::{Lives, LivesCount};
+use crate::game::daily::DailyExported;
+use crate::game::hotseat::{self, HotSeat, TurnText};
+use crate::game::bot::{Bot, BotScore};
 use crate::game::load::Rounds;
+use crate::game::answer::WagerHistory;
+use crate::scores::{self, HighScore};
+use crate::stats::{self, GameRecord, QuestionRecord};
+use crate::storage::unix_time;
 
 pub struct UiPlugin;
 
 #[derive(Component)]
 struct UiRoot;
 #[derive(Component)]
-pub struct QuestionCount(pub u8);
+pub struct QuestionCount(pub u32);
 #[derive(Component)]
-pub struct ScoreCount(pub u8);
+pub struct ScoreCount(pub i32);
 #[derive(Component)]
 struct ScoreCard;
+#[derive(Component)]
+struct ScoreText;
+// UI nodes laid over the final scorecard
+#[derive(Component)]
+struct ScoreCardUi;
+#[derive(Component, Clone, Copy)]
+enum ScoreCardButton {
+    Review,
+    Menu,
+}
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
@@ -27,7 +49,10 @@ impl Plugin for UiPlugin {
                SystemSet::on_enter(AppState::Game).with_system(setup_ui))
            .add_system_set(
                SystemSet::on_update(AppState::Game).with_system(final_scorecard)
-                                                   .with_system(return_to_menu))
+                                                   .with_system(calibration_chart)
+                                                   .with_system(record_score)
+                                                   .with_system(scorecard_buttons)
+                                                   .with_system(show_daily_export))
            .add_system_set(
                SystemSet::on_exit(AppState::Game).with_system(teardown_ui));
     }
@@ -35,7 +60,18 @@ impl Plugin for UiPlugin {
 
 fn setup_ui(mut cmds: Commands,
             asset_server: Res<AssetServer>,
+            rounds: Res<Rounds>,
+            settings: Res<GameSettings>,
 ) {
+    // Slots shrink once there are too many to fit at their usual size
+    let slot_height = (100. / settings.token_count as f32).min(20.);
+    // Hot seat needs room for every player's score, so its counters take more space
+    let (counter_height, score_text, score_size) = if settings.mode == GameMode::HotSeat {
+        (40., hotseat::scoreboard(&vec![0; settings.players]), 18.)
+    } else {
+        (25., String::from("Score: 0"), 24.)
+    };
+
     cmds.spawn_bundle(NodeBundle {
         style: Style {
             size: Size::new(Val::Percent(100.), Val::Percent(100.)),
@@ -60,7 +96,7 @@ fn setup_ui(mut cmds: Commands,
             // Counter Text Container
             parent.spawn_bundle(NodeBundle {
                 style: Style {
-                    size: Size::new(Val::Percent(100.), Val::Percent(25.)),
+                    size: Size::new(Val::Percent(100.), Val::Percent(counter_height)),
                     flex_direction: FlexDirection::ColumnReverse,
                     ..Default::default()
                 },
@@ -74,7 +110,7 @@ fn setup_ui(mut cmds: Commands,
                         ..Default::default()
                     },
                     text: Text::with_section(
-                        "Question: 1/8",
+                        question_count_text(1, &rounds, &settings),
                         TextStyle {
                             font: asset_server.load("fonts/PublicSans-Medium.ttf"),
                             font_size: 24.,
@@ -85,6 +121,26 @@ fn setup_ui(mut cmds: Commands,
                     ..Default::default()
                 }).insert(QuestionCount(1));
 
+                // Turn Text, naming the player whose turn it is in hot seat
+                if settings.mode == GameMode::HotSeat {
+                    parent.spawn_bundle(TextBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(5.)),
+                            ..Default::default()
+                        },
+                        text: Text::with_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                                font_size: 24.,
+                                color: Color::BLACK,
+                            },
+                            Default::default()
+                        ),
+                        ..Default::default()
+                    }).insert(TurnText);
+                }
+
                 // Score Counter Text
                 parent.spawn_bundle(TextBundle {
                     style: Style {
@@ -92,16 +148,56 @@ fn setup_ui(mut cmds: Commands,
                         ..Default::default()
                     },
                     text: Text::with_section(
-                        "Score: 0",
+                        score_text,
                         TextStyle {
                             font: asset_server.load("fonts/PublicSans-Medium.ttf"),
-                            font_size: 24.,
+                            font_size: score_size,
                             color: Color::BLACK,
                         },
                         Default::default()
                     ),
                     ..Default::default()
                 }).insert(ScoreCount(0));
+
+                // Bot Score Text, beside the player's in versus
+                if settings.mode == GameMode::Versus {
+                    parent.spawn_bundle(TextBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(5.)),
+                            ..Default::default()
+                        },
+                        text: Text::with_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                                font_size: 24.,
+                                color: Color::BLACK,
+                            },
+                            Default::default()
+                        ),
+                        ..Default::default()
+                    }).insert(BotScore);
+                }
+
+                // Lives Counter Text
+                if settings.mode == GameMode::Survival {
+                    parent.spawn_bundle(TextBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(5.)),
+                            ..Default::default()
+                        },
+                        text: Text::with_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                                font_size: 24.,
+                                color: Color::BLACK,
+                            },
+                            Default::default()
+                        ),
+                        ..Default::default()
+                    }).insert(LivesCount);
+                }
             });
 
             // Token Slots
@@ -110,17 +206,19 @@ fn setup_ui(mut cmds: Commands,
                     flex_direction: FlexDirection::Column,
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::FlexEnd,
-                    size: Size::new(Val::Percent(100.), Val::Percent(75.)),
+                    size: Size::new(Val::Percent(100.), 
+                                    Val::Percent(100. - counter_height)),
                     ..Default::default()
                 },
                 color: Color::NONE.into(),
                 ..Default::default()
             }).with_children(|parent| {
-                for _ in 0..5 {
+                for _ in 0..settings.token_count {
                     parent.spawn_bundle(NodeBundle {
                         style: Style {
                             margin: Rect::all(Val::Px(2.5)),
-                            size: Size::new(Val::Percent(50.), Val::Percent(20.)),
+                            size: Size::new(Val::Percent(50.), 
+                                            Val::Percent(slot_height)),
                             ..Default::default()
                         },
                         color: Color::NONE.into(),
@@ -155,34 +253,16 @@ fn setup_ui(mut cmds: Commands,
                 ..Default::default()
             }).insert(QuestionSlot);
             
-            for _ in 0..2 {
-                // Row Answer Container
-                parent.spawn_bundle(NodeBundle {
-                    style: Style {
-                        padding: Rect::all(Val::Px(5.)),
-                        size: Size::new(Val::Percent(100.), Val::Percent(30.)),
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
+            // Answer Grid, filled with AnswerSlots to suit each Question
+            parent.spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::ColumnReverse,
+                    size: Size::new(Val::Percent(100.), Val::Percent(60.)),
                     ..Default::default()
-                }).with_children(|parent| {
-                    for _ in 0..2 {
-                        // Answer Box
-                        parent.spawn_bundle(NodeBundle {
-                            style: Style {
-                                align_items: AlignItems::Center,
-                                justify_content: JustifyContent::Center,
-                                margin: Rect::all(Val::Px(5.)),
-                                padding: Rect::all(Val::Px(5.)),
-                                size: Size::new(Val::Percent(50.), Val::Percent(100.)),
-                                ..Default::default()
-                            },
-                            color: Color::NONE.into(),
-                            ..Default::default()
-                        }).insert(AnswerSlot);
-                    }
-                });
-            }
+                },
+                color: Color::NONE.into(),
+                ..Default::default()
+            }).insert(AnswerGrid);
 
             // Submit Button
             parent.spawn_bundle(ButtonBundle {
@@ -215,28 +295,85 @@ fn setup_ui(mut cmds: Commands,
         // Right Border
         parent.spawn_bundle(NodeBundle {
             style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: Rect::all(Val::Px(20.)),
                 size: Size::new(Val::Percent(15.), Val::Percent(100.)),
                 ..Default::default()
             },
             color: Color::NONE.into(),
             ..Default::default()
 
+        }).with_children(|parent| {
+            // Countdown Bar, draining downwards as time runs out in timed mode
+            if settings.time_limit.is_some() {
+                parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(30.), Val::Percent(100.)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }).insert(CountdownBar);
+            }
         });
     }).insert(UiRoot);
 }
 
 // Spawns a final scorecard when all rounds are completed
+#[allow(clippy::too_many_arguments)]
 fn final_scorecard(score_q: Query<&ScoreCount>,
                    windows: Res<Windows>,
+                   button_colors: Res<ButtonMaterials>,
                    asset_server: Res<AssetServer>,
                    mut cmds: Commands,
                    rounds: Res<Rounds>,
+                   lives: Res<Lives>,
+                   hot_seat: Res<HotSeat>,
+                   bot: Res<Bot>,
+                   settings: Res<GameSettings>,
 ) {
     if rounds.is_changed() && rounds.round_number == rounds.round_max {
         let window = windows.get_primary().unwrap();
         let x_dim = window.width() / 2.;
-        let y_dim = window.height() / 2.;
+        // Tall enough to hold the calibration chart and buttons below the score
+        let y_dim = window.height() * 0.7;
         let score = score_q.single();
+        let max_score = rounds.round_max as i32 
+            * settings.scoring.max_score(settings.token_count);
+        let headline = match settings.mode {
+            GameMode::Standard | GameMode::Daily | GameMode::HotSeat => String::new(),
+            GameMode::Survival => {
+                let lost = if lives.remaining == 0 { 1 } else { 0 };
+                format!("Survived {} Questions!\n", rounds.round_max - lost)
+            },
+            GameMode::Versus => {
+                let result = match score.0.cmp(&bot.score) {
+                    std::cmp::Ordering::Greater => "You Win!",
+                    std::cmp::Ordering::Less => "The Bot Wins!",
+                    std::cmp::Ordering::Equal => "It's a Draw!",
+                };
+                format!("{} The {} bot scored {}\n", result, settings.bot, bot.score)
+            },
+        };
+        // Hot seat has no calibration chart, so its scoreboard fills the card
+        let (card_text, text_y) = if settings.mode == GameMode::HotSeat {
+            (format!("{}\n\n{}\n\nOut of {} Points each, scored by the {} rule",
+                     hotseat::winner_text(&hot_seat.scores),
+                     hotseat::scoreboard(&hot_seat.scores), max_score,
+                     settings.scoring.name()), 20.)
+        } else {
+            (format!("{}Final Score: {} of {} Points!\n\
+                      Scored by the {} rule\n\n\
+                      How often answers wagered each number \
+                      of tokens were correct:", headline, score.0,
+                     max_score, settings.scoring.name()), y_dim / 4.)
+        };
+        // Hot seat games are shared, so there is no single player's wagers to review
+        let buttons: &[(&str, ScoreCardButton)] = if settings.mode == GameMode::HotSeat {
+            &[("Back to Menu", ScoreCardButton::Menu)]
+        } else {
+            &[("Review", ScoreCardButton::Review), ("Back to Menu", ScoreCardButton::Menu)]
+        };
 
         cmds.spawn_bundle(SpriteBundle {
             sprite: Sprite {
@@ -263,27 +400,19 @@ fn final_scorecard(score_q: Query<&ScoreCount>,
                 ..Default::default()
             });
 
+            // Score Text, above the calibration chart
             parent.spawn_bundle(Text2dBundle {
-                transform: Transform::from_xyz(0., 0., 55.),
+                transform: Transform::from_xyz(0., text_y, 55.),
                 text: Text {
                     sections: vec![
                         TextSection {
-                            value: format!("Final Score: {} Points!\n\n", score.0),
-                            style: TextStyle {
-                                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
-                                font_size: 24.,
-                                color: Color::BLACK,
-                            },
-                        },
-                        TextSection {
-                            value: String::from("Click anywhere to continue..."),
+                            value: card_text,
                             style: TextStyle {
                                 font: asset_server.load("fonts/PublicSans-Medium.ttf"),
                                 font_size: 24.,
                                 color: Color::BLACK,
                             },
                         },
-
                     ],
                     alignment: TextAlignment {
                         vertical: VerticalAlign::Center,
@@ -291,30 +420,283 @@ fn final_scorecard(score_q: Query<&ScoreCount>,
                     },
                 },
                 text_2d_bounds: Text2dBounds {
-                    size: Size::new(x_dim - 5., y_dim - 5.),
+                    size: Size::new(x_dim - 5., y_dim / 2.),
                 },
                 ..Default::default()
-            });
+            }).insert(ScoreText);
         }).insert(ScoreCard);
+
+        // Button Row, along the bottom of the scorecard
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(x_dim / 2.),
+                    bottom: Val::Px((window.height() - y_dim) / 2. + 10.),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Px(x_dim), Val::Px(45.)),
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            for &(text, button) in buttons {
+                parent.spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(170.), Val::Px(45.)),
+                        margin: Rect {
+                            left: Val::Px(10.),
+                            right: Val::Px(10.),
+                            ..Default::default()
+                        },
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: button_colors.none,
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            text,
+                            TextStyle {
+                                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                                font_size: 24.,
+                                color: Color::rgb(1., 1., 1.),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(button);
+            }
+        }).insert(ScoreCardUi);
+    }
+}
+
+// Spawns a bar chart in the lower half of the final scorecard showing, for every
+// number of tokens, how often answers wagered that many were correct. A line across
+// each bar marks where a perfectly calibrated player would reach
+fn calibration_chart(windows: Res<Windows>,
+                     asset_server: Res<AssetServer>,
+                     mut cmds: Commands,
+                     rounds: Res<Rounds>,
+                     history: Res<WagerHistory>,
+                     settings: Res<GameSettings>,
+) {
+    if !(rounds.is_changed() && rounds.round_number == rounds.round_max)
+       || settings.mode == GameMode::HotSeat {
+        return;
+    }
+
+    let window = windows.get_primary().unwrap();
+    let x_dim = window.width() / 2.;
+    let y_dim = window.height() * 0.7;
+    let font = asset_server.load("fonts/PublicSans-Medium.ttf");
+
+    // Tally (wagered, correct) for every number of tokens an answer could hold
+    let mut tallies = vec![(0, 0); settings.token_count];
+    for round in history.0.iter() {
+        for (i, &wager) in round.wagers.iter().enumerate() {
+            if wager > 0 && wager <= settings.token_count {
+                tallies[wager - 1].0 += 1;
+                if i == round.correct {
+                    tallies[wager - 1].1 += 1;
+                }
+            }
+        }
+    }
+
+    cmds.spawn_bundle(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Px(x_dim / 2. + 20.),
+                bottom: Val::Px((window.height() - y_dim) / 2. + 65.),
+                ..Default::default()
+            },
+            size: Size::new(Val::Px(x_dim - 40.), Val::Px(y_dim / 2. - 75.)),
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        for (level, &(wagered, correct)) in tallies.iter().enumerate() {
+            let tokens = level + 1;
+            let expected = 100. * tokens as f32 / settings.token_count as f32;
+            let (actual, label) = if wagered > 0 {
+                let actual = 100. * correct as f32 / wagered as f32;
+                (actual, format!("{:.0}%", actual))
+            } else {
+                (0., String::from("-"))
+            };
+
+            // Bar Column, read from the bottom up
+            parent.spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    size: Size::new(Val::Percent(100. / settings.token_count as f32),
+                                    Val::Percent(100.)),
+                    ..Default::default()
+                },
+                color: Color::NONE.into(),
+                ..Default::default()
+            }).with_children(|parent| {
+                // Token Count Label
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        tokens.to_string(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.,
+                            color: Color::BLACK,
+                        },
+                        Default::default()
+                    ),
+                    ..Default::default()
+                });
+
+                // Bar Background
+                parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        size: Size::new(Val::Percent(60.), Val::Percent(70.)),
+                        ..Default::default()
+                    },
+                    color: Color::rgb(0.9, 0.9, 0.9).into(),
+                    ..Default::default()
+                }).with_children(|parent| {
+                    // Bar
+                    parent.spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.), Val::Percent(actual)),
+                            ..Default::default()
+                        },
+                        color: Color::PURPLE.into(),
+                        ..Default::default()
+                    });
+
+                    // Expected Line
+                    parent.spawn_bundle(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: Rect {
+                                bottom: Val::Percent(expected),
+                                ..Default::default()
+                            },
+                            size: Size::new(Val::Percent(100.), Val::Px(2.)),
+                            ..Default::default()
+                        },
+                        color: Color::BLACK.into(),
+                        ..Default::default()
+                    });
+                });
+
+                // Percent Correct Label
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        label,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.,
+                            color: Color::BLACK,
+                        },
+                        Default::default()
+                    ),
+                    ..Default::default()
+                });
+            });
+        }
+    }).insert(ScoreCardUi);
+}
+
+// Saves the final score to the high score table, and the game to the lifetime
+// history, once the game is over
+fn record_score(score_q: Query<&ScoreCount>,
+                rounds: Res<Rounds>,
+                history: Res<WagerHistory>,
+                settings: Res<GameSettings>,
+) {
+    // Hot seat scores belong to no single player, so are left out of both
+    if rounds.is_changed() && rounds.round_number == rounds.round_max
+       && settings.mode != GameMode::HotSeat {
+        let score = score_q.single().0;
+        let max_score = rounds.round_max as i32 
+            * settings.scoring.max_score(settings.token_count);
+        scores::record(HighScore::new(score, max_score, rounds.round_max, &settings));
+
+        stats::record(GameRecord {
+            played_at: unix_time(),
+            score,
+            max_score,
+            questions: rounds.questions.iter().zip(history.0.iter())
+                .map(|(question, round)| QuestionRecord {
+                    category: question.category.clone(),
+                    difficulty: question.difficulty.clone(),
+                    token_count: settings.token_count,
+                    tokens_correct: round.wagers[round.correct],
+                })
+                .collect(),
+        });
     }
 }
 
-// Returns to the main menu on any left click if the game is over
-fn return_to_menu(rounds: Res<Rounds>, 
-                  mouse_button: Res<Input<MouseButton>>,
-                  mut appstate: ResMut<State<AppState>>,
+// Heads the scorecard with where the daily summary was saved, once it's written
+fn show_daily_export(mut exported: EventReader<DailyExported>,
+                     mut text_q: Query<&mut Text, With<ScoreText>>,
 ) {
-    if mouse_button.just_pressed(MouseButton::Left) &&
-       rounds.round_number == rounds.round_max {
-        appstate.set(AppState::Menu).unwrap();
+    for DailyExported(path) in exported.iter() {
+        for mut text in text_q.iter_mut() {
+            let style = text.sections[0].style.clone();
+            text.sections.insert(0, TextSection {
+                value: format!("Daily summary saved as {}\n",
+                               path.file_name().unwrap_or_default().to_string_lossy()),
+                style,
+            });
+        }
+    }
+}
+
+// Click handler for the scorecard's buttons, once the game is over
+fn scorecard_buttons(mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor, &ScoreCardButton),
+                                      Pressed>,
+                     button_colors: Res<ButtonMaterials>,
+                     mut appstate: ResMut<State<AppState>>,
+) {
+    for (interaction, pad_click, mut color, button) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                match button {
+                    ScoreCardButton::Review => appstate.set(AppState::Review).unwrap(),
+                    ScoreCardButton::Menu => appstate.set(AppState::Menu).unwrap(),
+                }
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            }
+        }
     }
 }
 
 // Despawns all elements of the Ui 
 fn teardown_ui(ui_q: Query<Entity, With<UiRoot>>,
                scorecard_q: Query<Entity, With<ScoreCard>>,
+               scorecard_ui_q: Query<Entity, With<ScoreCardUi>>,
                mut cmds: Commands,
 ) {
+    for scorecard_ui in scorecard_ui_q.iter() {
+        cmds.entity(scorecard_ui).despawn_recursive();
+    }
+
     let root = ui_q.single();
     cmds.entity(root).despawn_recursive();
 
@@ -0,0 +1,351 @@
+use bevy::prelude::*;
+
+use crate::{AppState, ButtonMaterials};
+use crate::game::answer::{
+    self,
+    AnswerBlock,
+    AnswerFocus,
+    FocusMove,
+    Highlight,
+    SubmitButton,
+    SubmitPressed,
+};
+use crate::game::hotseat::PassScreen;
+use crate::game::load::Rounds;
+use crate::game::token::TokenAction;
+
+// How far the left stick must be pushed before it counts as a press
+const STICK_THRESHOLD: f32 = 0.5;
+
+pub struct GamepadPlugin;
+
+// The button picked out by the gamepad on screens without a board, tracked apart
+// from the mouse's Interaction
+#[derive(Default)]
+pub struct PadFocus(pub Option<Entity>);
+
+// Marks a button pressed from the gamepad, for a single frame
+#[derive(Component)]
+pub struct PadClick;
+
+// Filter for button handlers, so that they act on the gamepad as well as the mouse
+pub type Pressed = Or<(Changed<Interaction>, Added<PadClick>)>;
+
+// A press on any connected gamepad, with the D-pad and left stick read alike
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PadInput {
+    Move(FocusMove),
+    // Places a token, or clicks the focused button
+    South,
+    // Takes a token back
+    East,
+    // Clears every answer
+    West,
+    // Submits the wagers
+    Start,
+}
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PadInput>()
+           .init_resource::<PadFocus>()
+           .add_system(read_gamepads)
+           .add_system(pad_buttons)
+           .add_system_to_stage(CoreStage::PostUpdate, show_pad_focus)
+           .add_system_set(
+               SystemSet::on_update(AppState::Game).with_system(pad_tokens));
+    }
+}
+
+// The Interaction a button handler should act on, counting a PadClick as a click
+pub fn interaction(interaction: &Interaction, pad_click: Option<&PadClick>) -> Interaction {
+    if pad_click.is_some() {
+        Interaction::Clicked
+    } else {
+        *interaction
+    }
+}
+
+// Turns gamepad buttons and stick pushes into PadInputs. The stick only counts
+// once per push, so must return to rest before moving again
+fn read_gamepads(gamepads: Res<Gamepads>,
+                 buttons: Res<Input<GamepadButton>>,
+                 axes: Res<Axis<GamepadAxis>>,
+                 mut pad_input: EventWriter<PadInput>,
+                 mut stick_held: Local<Vec<(Gamepad, Option<FocusMove>)>>,
+) {
+    let pressed = [(GamepadButtonType::DPadLeft, PadInput::Move(FocusMove::Left)),
+                   (GamepadButtonType::DPadRight, PadInput::Move(FocusMove::Right)),
+                   (GamepadButtonType::DPadUp, PadInput::Move(FocusMove::Up)),
+                   (GamepadButtonType::DPadDown, PadInput::Move(FocusMove::Down)),
+                   (GamepadButtonType::South, PadInput::South),
+                   (GamepadButtonType::East, PadInput::East),
+                   (GamepadButtonType::West, PadInput::West),
+                   (GamepadButtonType::Start, PadInput::Start)];
+
+    for &gamepad in gamepads.iter() {
+        for (button_type, input) in pressed {
+            if buttons.just_pressed(GamepadButton(gamepad, button_type)) {
+                pad_input.send(input);
+            }
+        }
+
+        let x = axes.get(GamepadAxis(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.);
+        let y = axes.get(GamepadAxis(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.);
+
+        let held = match stick_held.iter_mut().find(|(held_pad, _)| *held_pad == gamepad) {
+            Some((_, held)) => held,
+            None => {
+                stick_held.push((gamepad, None));
+                &mut stick_held.last_mut().unwrap().1
+            },
+        };
+        if let Some(step) = stick_push(held, stick_direction(x, y)) {
+            pad_input.send(PadInput::Move(step));
+        }
+    }
+}
+
+// The way the stick at x, y is pushed, along whichever axis it's pushed furthest
+fn stick_direction(x: f32, y: f32) -> Option<FocusMove> {
+    if x.abs().max(y.abs()) < STICK_THRESHOLD {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x < 0. { FocusMove::Left } else { FocusMove::Right })
+    } else {
+        Some(if y < 0. { FocusMove::Down } else { FocusMove::Up })
+    }
+}
+
+// The move made by the stick now pointing in direction, having been held in held,
+// which only counts when the stick's direction changes
+fn stick_push(held: &mut Option<FocusMove>, direction: Option<FocusMove>) -> Option<FocusMove> {
+    if *held == direction {
+        return None;
+    }
+
+    *held = direction;
+    direction
+}
+
+// Whether the gamepad is wagering on the board rather than moving between buttons
+fn wagering(state: &State<AppState>, rounds: Option<&Rounds>, passing: bool) -> bool {
+    *state.current() == AppState::Game && !passing
+        && rounds.is_some_and(|rounds| rounds.round_number < rounds.round_max)
+}
+
+// Moves the PadFocus between the buttons on screen, and presses the focused one with
+// South. Buttons are picked by whichever lies nearest in the direction pressed
+#[allow(clippy::too_many_arguments)]
+fn pad_buttons(mut pad_input: EventReader<PadInput>,
+               button_query: Query<(Entity, &GlobalTransform, &Visibility), With<Button>>,
+               click_query: Query<Entity, With<PadClick>>,
+               pass_query: Query<With<PassScreen>>,
+               state: Res<State<AppState>>,
+               rounds: Option<Res<Rounds>>,
+               mut focus: ResMut<PadFocus>,
+               mut cmds: Commands,
+) {
+    // A press lasts a single frame, as a mouse click released straight away would
+    for click_id in click_query.iter() {
+        cmds.entity(click_id).remove::<PadClick>();
+    }
+
+    let inputs: Vec<PadInput> = pad_input.iter().copied().collect();
+    if inputs.is_empty() || wagering(&state, rounds.as_deref(), !pass_query.is_empty()) {
+        return;
+    }
+
+    // Forget a focused button which has gone or been hidden
+    let focus_shown = focus.0.and_then(|focus_id| button_query.get(focus_id).ok())
+        .is_some_and(|(_, _, visibility)| visibility.is_visible);
+    if !focus_shown {
+        focus.0 = None;
+    }
+
+    for input in inputs {
+        match input {
+            PadInput::Move(step) => {
+                let buttons: Vec<(Entity, Vec2)> = button_query.iter()
+                    .filter(|(_, _, visibility)| visibility.is_visible)
+                    .map(|(button_id, gt, _)| (button_id, gt.translation.truncate()))
+                    .collect();
+                let target = match focus.0.and_then(|focus_id| {
+                    buttons.iter().find(|(button_id, _)| *button_id == focus_id)
+                }) {
+                    Some(&(focus_id, from)) => nearest_button(&buttons, focus_id, from, step),
+                    // Begin at the top left, UI coordinates running upwards
+                    None => buttons.iter()
+                        .max_by(|(_, a), (_, b)| (a.y - a.x).total_cmp(&(b.y - b.x)))
+                        .map(|(button_id, _)| *button_id),
+                };
+
+                if target.is_some() {
+                    focus.0 = target;
+                }
+            },
+            PadInput::South => {
+                if let Some(focus_id) = focus.0 {
+                    cmds.entity(focus_id).insert(PadClick);
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+// Shows the PadFocus in the hovered color, painting over whatever the button's own
+// handler last left it, and returns a button the focus has left to its resting color.
+// Runs after every handler, so that none of them can paint over it
+fn show_pad_focus(mut button_query: Query<(&Interaction, &mut UiColor, Option<&PadClick>),
+                                          With<Button>>,
+                  focus: Res<PadFocus>,
+                  button_colors: Res<ButtonMaterials>,
+                  mut shown: Local<Option<Entity>>,
+) {
+    if *shown != focus.0 {
+        if let Some(Ok((interaction, mut color, _))) = shown.map(|shown_id| {
+            button_query.get_mut(shown_id)
+        }) {
+            if *interaction == Interaction::None {
+                *color = button_colors.none;
+            }
+        }
+        *shown = focus.0;
+    }
+
+    // A pressed button keeps its clicked color for the frame it is pressed
+    if let Some(Ok((interaction, mut color, None))) = focus.0.map(|focus_id| {
+        button_query.get_mut(focus_id)
+    }) {
+        if *interaction == Interaction::None && color.0 != button_colors.hovered.0 {
+            *color = button_colors.hovered;
+        }
+    }
+}
+
+// The button nearest to from in the direction of step, favouring those most nearly
+// in line with it
+fn nearest_button(buttons: &[(Entity, Vec2)],
+                  from_id: Entity,
+                  from: Vec2,
+                  step: FocusMove,
+) -> Option<Entity> {
+    let direction = match step {
+        FocusMove::Left => Vec2::new(-1., 0.),
+        FocusMove::Right => Vec2::new(1., 0.),
+        FocusMove::Up => Vec2::new(0., 1.),
+        FocusMove::Down => Vec2::new(0., -1.),
+    };
+
+    buttons.iter()
+        .filter(|(button_id, _)| *button_id != from_id)
+        .filter_map(|&(button_id, pos)| {
+            let offset = pos - from;
+            let along = offset.dot(direction);
+            let across = offset.perp_dot(direction).abs();
+            (along > 0.).then_some((button_id, along + 2. * across))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(button_id, _)| button_id)
+}
+
+// Wagers on the board from the gamepad: the D-pad or stick moves the AnswerFocus,
+// South places a token on the focused answer, East takes one back, West clears
+// every answer, and Start submits once every token is placed
+#[allow(clippy::too_many_arguments)]
+fn pad_tokens(mut pad_input: EventReader<PadInput>,
+              answer_query: Query<&AnswerBlock>,
+              highlight_query: Query<With<Highlight>>,
+              pass_query: Query<With<PassScreen>>,
+              submit_query: Query<&Visibility, (With<SubmitButton>, With<Button>)>,
+              state: Res<State<AppState>>,
+              rounds: Res<Rounds>,
+              mut focus: ResMut<AnswerFocus>,
+              mut token_actions: EventWriter<TokenAction>,
+              mut submit_pressed: EventWriter<SubmitPressed>,
+              mut windows: ResMut<Windows>,
+) {
+    let inputs: Vec<PadInput> = pad_input.iter().copied().collect();
+    // The board is settled once submitted, and hidden while being passed on
+    if inputs.is_empty() || !highlight_query.is_empty()
+       || !wagering(&state, Some(&rounds), !pass_query.is_empty()) {
+        return;
+    }
+
+    let answer_count = answer_query.iter().count();
+    for input in inputs {
+        match input {
+            PadInput::Move(step) => {
+                let moved = answer::move_focus(focus.0, step, answer_count);
+                answer::set_focus(&mut focus, moved);
+            },
+            PadInput::South | PadInput::East => {
+                // With nothing focused yet, the first answer is
+                if focus.0.map_or(true, |index| index >= answer_count) {
+                    answer::set_focus(&mut focus, (answer_count > 0).then_some(0));
+                }
+                if let Some(index) = focus.0 {
+                    token_actions.send(if input == PadInput::South {
+                        TokenAction::Place(index)
+                    } else {
+                        TokenAction::Remove(index)
+                    });
+                }
+            },
+            PadInput::West => token_actions.send(TokenAction::Clear),
+            PadInput::Start => {
+                if submit_query.iter().any(|visibility| visibility.is_visible) {
+                    answer::press_submit(&mut windows, &mut submit_pressed);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_points_along_its_furthest_axis() {
+        assert_eq!(stick_direction(0.2, -0.3), None);
+        assert_eq!(stick_direction(0.9, 0.6), Some(FocusMove::Right));
+        assert_eq!(stick_direction(-0.6, 0.1), Some(FocusMove::Left));
+        assert_eq!(stick_direction(0.5, 0.7), Some(FocusMove::Up));
+        assert_eq!(stick_direction(0., -1.), Some(FocusMove::Down));
+    }
+
+    #[test]
+    fn stick_moves_once_per_push() {
+        let mut held = None;
+
+        assert_eq!(stick_push(&mut held, Some(FocusMove::Right)), Some(FocusMove::Right));
+        // Held in place, the stick doesn't repeat
+        assert_eq!(stick_push(&mut held, Some(FocusMove::Right)), None);
+        assert_eq!(stick_push(&mut held, None), None);
+        assert_eq!(stick_push(&mut held, Some(FocusMove::Right)), Some(FocusMove::Right));
+        // Swung straight round to another direction, it moves again
+        assert_eq!(stick_push(&mut held, Some(FocusMove::Down)), Some(FocusMove::Down));
+    }
+
+    #[test]
+    fn nearest_button_favours_those_in_line() {
+        let ids: Vec<Entity> = (0..4).map(Entity::from_raw).collect();
+        // A column of two buttons, with a third below and off to the right
+        let buttons = [(ids[0], Vec2::new(100., 300.)),
+                       (ids[1], Vec2::new(100., 200.)),
+                       (ids[2], Vec2::new(250., 180.)),
+                       (ids[3], Vec2::new(400., 300.))];
+
+        assert_eq!(nearest_button(&buttons, ids[0], buttons[0].1, FocusMove::Down),
+                   Some(ids[1]));
+        assert_eq!(nearest_button(&buttons, ids[0], buttons[0].1, FocusMove::Right),
+                   Some(ids[3]));
+        assert_eq!(nearest_button(&buttons, ids[1], buttons[1].1, FocusMove::Right),
+                   Some(ids[2]));
+        // Nothing lies further up or left
+        assert_eq!(nearest_button(&buttons, ids[0], buttons[0].1, FocusMove::Up), None);
+        assert_eq!(nearest_button(&buttons, ids[0], buttons[0].1, FocusMove::Left), None);
+    }
+}
@@ -2,6 +2,8 @@ use bevy::prelude::*;
 use bevy::text::Text2dBounds;
 
 use crate::{ButtonMaterials, AppState};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::setup::GameSettings;
 
 pub struct HelpPlugin;
 
@@ -22,6 +24,7 @@ impl Plugin for HelpPlugin {
 // Spawns help info text and back button
 fn spawn_help_menu(asset_server: Res<AssetServer>,
                    windows: Res<Windows>,
+                   settings: Res<GameSettings>,
                    mut cmds: Commands
 ) {
     let window = windows.get_primary().unwrap();
@@ -31,17 +34,22 @@ fn spawn_help_menu(asset_server: Res<AssetServer>,
     // Help Text
     cmds.spawn_bundle(Text2dBundle {
        text: Text::with_section(
-             "PrettySure is a trivia game where you place your bets upon \
+             format!("PrettySure is a trivia game where you place your bets upon \
               various answers to the question posed using your \"tokens.\"\n\n\
               A turn is played by using left click to drag your tokens, \
-              located on the lefthand side of the screen, onto one of the four \
+              located on the lefthand side of the screen, onto any of the \
               answer boxes. The token will take on the color of the answer it \
-              is on top of when properly placed. Once all five tokens have been \
+              is on top of when properly placed. You can also press an \
+              answer's number key to wager a token on it, or Shift and the \
+              number to take one back. Once all {} tokens have been \
               placed on top of an answer, a submit button will appear at the \
-              bottom of the screen, allowing you to lock in your answer and see \
-              the correct one highlighted.\n\nPlay through all ten questions, and \
-              try to get as close as possible to the maximum score of 40 \
-              points!",
+              bottom of the screen (or press Enter), allowing you to lock in \
+              your answer and see the correct one highlighted.\n\nPlay \
+              through all {} questions, and try to get as close as possible \
+              to the maximum score of {} \
+              points!", settings.token_count, settings.question_count, 
+              settings.question_count as i32
+                  * settings.scoring.max_score(settings.token_count)),
             TextStyle {
                 font: asset_server.load("fonts/PublicSans-Medium.ttf"),
                 font_size: 25.,
@@ -130,13 +138,14 @@ fn spawn_help_menu(asset_server: Res<AssetServer>,
 }
 
 // Click handler for back to AppState::Menu button
+#[allow(clippy::type_complexity)]
 fn back_button(mut state: ResMut<State<AppState>>, 
-               mut query: Query<(&Interaction, &mut UiColor),
-                                (Changed<Interaction>, With<Button>)>,
+               mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                (Pressed, With<Button>)>,
                button_colors: Res<ButtonMaterials>,               
 ) {
-    for (interaction, mut color) in query.iter_mut() {
-        match interaction {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
             Interaction::Clicked => {
                 *color = button_colors.clicked;
                 state.set(AppState::Menu).unwrap();
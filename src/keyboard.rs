@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::AppState;
+use crate::game::answer::{
+    self,
+    AnswerBlock,
+    AnswerFocus,
+    FocusMove,
+    Highlight,
+    SubmitButton,
+    SubmitPressed,
+};
+use crate::game::token::TokenAction;
+use crate::game::load::Rounds;
+use crate::game::hotseat::PassScreen;
+
+// Keys naming each answer, in the order answers are laid out
+const ANSWER_KEYS: [(KeyCode, KeyCode); 6] = [
+    (KeyCode::Key1, KeyCode::Numpad1),
+    (KeyCode::Key2, KeyCode::Numpad2),
+    (KeyCode::Key3, KeyCode::Numpad3),
+    (KeyCode::Key4, KeyCode::Numpad4),
+    (KeyCode::Key5, KeyCode::Numpad5),
+    (KeyCode::Key6, KeyCode::Numpad6),
+];
+
+pub struct KeyboardPlugin;
+
+impl Plugin for KeyboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::Game).with_system(keyboard_tokens)
+                                                .with_system(keyboard_submit));
+    }
+}
+
+// Places and removes tokens from the keyboard. A number key puts a token on that
+// answer, or takes one off with Shift held, and Backspace clears every answer. The
+// arrow keys move the focus, which Space places on in the same way
+fn keyboard_tokens(keys: Res<Input<KeyCode>>,
+                   answer_query: Query<&AnswerBlock>,
+                   highlight_query: Query<With<Highlight>>,
+                   pass_query: Query<With<PassScreen>>,
+                   rounds: Res<Rounds>,
+                   mut focus: ResMut<AnswerFocus>,
+                   mut token_actions: EventWriter<TokenAction>,
+) {
+    // The board is settled once submitted or the game is over, and hidden while
+    // being passed on
+    if !highlight_query.is_empty() || !pass_query.is_empty()
+       || rounds.round_number >= rounds.round_max {
+        return;
+    }
+
+    let answer_count = answer_query.iter().count();
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let act = |index: usize| if shift {
+        TokenAction::Remove(index)
+    } else {
+        TokenAction::Place(index)
+    };
+
+    for (index, &(key, numpad)) in ANSWER_KEYS.iter().enumerate().take(answer_count) {
+        if keys.any_just_pressed([key, numpad]) {
+            answer::set_focus(&mut focus, Some(index));
+            token_actions.send(act(index));
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        token_actions.send(TokenAction::Clear);
+    }
+
+    for (key, step) in [(KeyCode::Left, FocusMove::Left), (KeyCode::Right, FocusMove::Right),
+                        (KeyCode::Up, FocusMove::Up), (KeyCode::Down, FocusMove::Down)] {
+        if keys.just_pressed(key) {
+            let moved = answer::move_focus(focus.0, step, answer_count);
+            answer::set_focus(&mut focus, moved);
+        }
+    }
+
+    if let Some(index) = focus.0 {
+        if keys.just_pressed(KeyCode::Space) && index < answer_count {
+            token_actions.send(act(index));
+        }
+    }
+}
+
+// Submits the wagers on Enter, so long as the submit button is showing
+fn keyboard_submit(keys: Res<Input<KeyCode>>,
+                   submit_query: Query<&Visibility, (With<SubmitButton>, With<Button>)>,
+                   highlight_query: Query<With<Highlight>>,
+                   mut submit_pressed: EventWriter<SubmitPressed>,
+                   mut windows: ResMut<Windows>,
+) {
+    // Already submitted wagers stay showing while the answer is revealed
+    if !keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter])
+       || !highlight_query.is_empty() {
+        return;
+    }
+
+    if submit_query.iter().any(|visibility| visibility.is_visible) {
+        answer::press_submit(&mut windows, &mut submit_pressed);
+    }
+}
@@ -3,18 +3,31 @@
 
 use bevy::prelude::*;
 
-use crate::{game::GamePlugin, menu::MenuPlugin, help::HelpPlugin};
+use crate::{game::GamePlugin, menu::MenuPlugin, help::HelpPlugin, setup::SetupPlugin,
+            scores::ScoresPlugin, stats::StatsPlugin, network::NetworkPlugin,
+            keyboard::KeyboardPlugin, gamepad::GamepadPlugin};
 
 mod menu;
 mod help;
+mod setup;
+mod scores;
+mod stats;
 mod game;
+mod network;
+mod keyboard;
+mod gamepad;
+mod storage;
 
 #[derive(PartialEq, Eq, Clone, Debug, Hash)]
 pub enum AppState {
     Menu,
     Help,
+    Setup,
     Load,
     Game,
+    Review,
+    HighScores,
+    Stats,
 }
 
 #[derive(Default)]
@@ -36,7 +49,13 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugin(MenuPlugin)
         .add_plugin(HelpPlugin)
+        .add_plugin(SetupPlugin)
+        .add_plugin(ScoresPlugin)
+        .add_plugin(StatsPlugin)
         .add_plugin(GamePlugin)
+        .add_plugin(NetworkPlugin)
+        .add_plugin(KeyboardPlugin)
+        .add_plugin(GamepadPlugin)
         .add_state(AppState::Menu)
         .add_startup_system(setup)
         .run();
@@ -1,12 +1,22 @@
 use bevy::prelude::*;
 
+use std::sync::Arc;
+
 use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::game::source::{OpenTdb, PackSource, Source};
 
 pub struct MenuPlugin;
 
 #[derive(Component)]
 struct PlayButton;
 #[derive(Component)]
+struct OfflineButton;
+#[derive(Component)]
+struct ScoresButton;
+#[derive(Component)]
+struct StatsButton;
+#[derive(Component)]
 struct HelpButton;
 
 struct MenuData {
@@ -19,6 +29,9 @@ impl Plugin for MenuPlugin {
                 SystemSet::on_enter(AppState::Menu).with_system(setup_menu))
             .add_system_set(
                 SystemSet::on_update(AppState::Menu).with_system(play_button)
+                                                    .with_system(offline_button)
+                                                    .with_system(scores_button)
+                                                    .with_system(stats_button)
                                                     .with_system(help_button))
             .add_system_set(
                 SystemSet::on_exit(AppState::Menu).with_system(teardown_menu));
@@ -72,74 +85,146 @@ fn setup_menu(mut cmds: Commands, asset_server: Res<AssetServer>) {
             ..Default::default()
         });
 
-        // Play Button
-        parent.spawn_bundle(ButtonBundle {
+        // Button Rows, wrapping onto a second row once three buttons are placed
+        parent.spawn_bundle(NodeBundle {
             style: Style {
-                size: Size::new(Val::Px(150.), Val::Px(50.)),
-                margin: Rect::all(Val::Auto),
+                size: Size::new(Val::Px(520.), Val::Auto),
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
+                flex_wrap: FlexWrap::Wrap,
                 ..Default::default()
             },
+            color: Color::NONE.into(),
             ..Default::default()
-        })
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text::with_section(
-                          "Play!",
-                          TextStyle {
-                              font: asset_server.load("fonts/PublicSans-Medium.ttf"),
-                              font_size: 40.,
-                              color: Color::rgb(1., 1., 1.),
-                          },
-                          Default::default(),
-                      ),
-                ..Default::default()
-            });
-        })
-        .insert(PlayButton);
+        }).with_children(|parent| {
+            let font = asset_server.load("fonts/PublicSans-Medium.ttf");
 
-        // Help Button
-        parent.spawn_bundle(ButtonBundle {
-            style: Style {
-                size: Size::new(Val::Px(150.), Val::Px(50.)),
-                margin: Rect::all(Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text::with_section(
-                          "Help?",
-                          TextStyle {
-                              font: asset_server.load("fonts/PublicSans-Medium.ttf"),
-                              font_size: 40.,
-                              color: Color::rgb(1., 1., 1.),
-                          },
-                          Default::default(),
-                      ),
-                ..Default::default()
-            });
-        })
-        .insert(HelpButton);
+            spawn_menu_button(parent, font.clone(), "Play!", PlayButton);
+            spawn_menu_button(parent, font.clone(), "Offline", OfflineButton);
+            spawn_menu_button(parent, font.clone(), "Scores", ScoresButton);
+            spawn_menu_button(parent, font.clone(), "Stats", StatsButton);
+            spawn_menu_button(parent, font, "Help?", HelpButton);
+        });
     }).id();
 
     cmds.insert_resource(MenuData{menu_handle});
 }
 
+// Spawns one of the main menu's buttons, labelled label and marked with button
+fn spawn_menu_button(parent: &mut ChildBuilder,
+                     font: Handle<Font>,
+                     label: &str,
+                     button: impl Component,
+) {
+    parent.spawn_bundle(ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(150.), Val::Px(50.)),
+            margin: Rect::all(Val::Px(10.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            text: Text::with_section(
+                label,
+                TextStyle {
+                    font,
+                    font_size: 40.,
+                    color: Color::rgb(1., 1., 1.),
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+    })
+    .insert(button);
+}
+
+#[allow(clippy::type_complexity)]
 fn play_button(mut state: ResMut<State<AppState>>, 
-               mut query: Query<(&Interaction, &mut UiColor),
-                                (Changed<Interaction>, With<PlayButton>)>,
+               mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                (Pressed, With<PlayButton>)>,
                button_colors: Res<ButtonMaterials>,               
+               mut cmds: Commands,
+) {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                cmds.insert_resource(Source(Arc::new(OpenTdb::default())));
+                state.set(AppState::Setup).unwrap();
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            }
+        }
+    }
+}
+
+// Plays using locally stored question packs rather than OpenTDB
+#[allow(clippy::type_complexity)]
+fn offline_button(mut state: ResMut<State<AppState>>, 
+                  mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                   (Pressed, With<OfflineButton>)>,
+                  button_colors: Res<ButtonMaterials>,               
+                  mut cmds: Commands,
+) {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                cmds.insert_resource(Source(Arc::new(PackSource::default())));
+                state.set(AppState::Setup).unwrap();
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn scores_button(mut state: ResMut<State<AppState>>,
+                 mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                  (Pressed, With<ScoresButton>)>,
+                 button_colors: Res<ButtonMaterials>,
+) {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                state.set(AppState::HighScores).unwrap();
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn stats_button(mut state: ResMut<State<AppState>>,
+                mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                 (Pressed, With<StatsButton>)>,
+                button_colors: Res<ButtonMaterials>,
 ) {
-    for (interaction, mut color) in query.iter_mut() {
-        match interaction {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
             Interaction::Clicked => {
                 *color = button_colors.clicked;
-                state.set(AppState::Load).unwrap();
+                state.set(AppState::Stats).unwrap();
             },
             Interaction::Hovered => {
                 *color = button_colors.hovered;
@@ -151,13 +236,14 @@ fn play_button(mut state: ResMut<State<AppState>>,
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn help_button(mut state: ResMut<State<AppState>>,
-               mut query: Query<(&Interaction, &mut UiColor),
-                                (Changed<Interaction>, With<HelpButton>)>,
+               mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                (Pressed, With<HelpButton>)>,
                button_colors: Res<ButtonMaterials>,
 ) {
-    for (interaction, mut color) in query.iter_mut() {
-        match interaction {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
             Interaction::Clicked => {
                 *color = button_colors.clicked;
                 state.set(AppState::Help).unwrap();
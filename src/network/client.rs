@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::AppState;
+use crate::setup::{GameMode, GameSettings};
+use crate::game::answer::WagerHistory;
+use crate::game::load::{Question, Rounds};
+use crate::game::scoring::{Linear, SCORING_RULES};
+use crate::network::Leaderboard;
+use crate::network::protocol::{Connection, Message, PROTOCOL_VERSION};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The connection to the host, along with the game it has sent
+struct Client {
+    conn: Connection,
+    name: Option<String>,
+    pending: Option<PendingGame>,
+    // The hosted game being played, and how many of its rounds have been submitted
+    playing: Option<(u32, usize)>,
+}
+
+// A game whose questions are still arriving, or which is waiting for the player to
+// return to the menu
+struct PendingGame {
+    id: u32,
+    question_count: usize,
+    token_count: usize,
+    scoring: String,
+    time_limit: Option<u32>,
+    time_bonus: bool,
+    questions: Vec<Question>,
+}
+
+// The player's own settings, put aside while a hosted game is played under the host's
+#[derive(Default)]
+struct SavedSettings(Option<GameSettings>);
+
+#[derive(Component)]
+struct StatusText;
+
+// Connects to the host at addr, carrying on offline should that fail
+pub fn build(app: &mut App, addr: &str, name: Option<String>) {
+    let conn = addr.to_socket_addrs()
+        .and_then(|mut addrs| addrs.next().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no address found")
+        }))
+        .and_then(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT))
+        .and_then(Connection::new);
+
+    match conn {
+        Ok(mut conn) => {
+            info!("Connected to host at {}", addr);
+            conn.send(&Message::Join {
+                version: PROTOCOL_VERSION,
+                name: name.clone(),
+            });
+            app.insert_resource(Client {
+                   conn,
+                   name,
+                   pending: None,
+                   playing: None,
+               })
+               .init_resource::<SavedSettings>()
+               .add_system(receive_messages)
+               .add_system_set(
+                   SystemSet::on_enter(AppState::Menu).with_system(spawn_status))
+               .add_system_set(
+                   SystemSet::on_update(AppState::Menu).with_system(start_game)
+                                                       .with_system(update_status))
+               .add_system_set(
+                   SystemSet::on_exit(AppState::Menu).with_system(teardown_status))
+               .add_system_set(
+                   SystemSet::on_update(AppState::Game).with_system(send_submissions))
+               .add_system_set(
+                   SystemSet::on_exit(AppState::Game).with_system(finish_game));
+        },
+        Err(error) => warn!("Couldn't join host at {}: {}", addr, error),
+    }
+}
+
+// Handles the host's messages, and goes offline should the connection close
+fn receive_messages(client: Option<ResMut<Client>>,
+                    mut leaderboard: ResMut<Leaderboard>,
+                    mut cmds: Commands,
+) {
+    let mut client = match client {
+        Some(client) => client,
+        None => return,
+    };
+
+    let messages = client.conn.receive()
+        .and_then(|messages| client.conn.flush().map(|_| messages));
+    let messages = match messages {
+        Ok(messages) => messages,
+        Err(error) => {
+            warn!("Lost connection to host: {}", error);
+            cmds.remove_resource::<Client>();
+            return;
+        },
+    };
+
+    for message in messages {
+        match message {
+            Message::Welcome { version, name } if version == PROTOCOL_VERSION => {
+                info!("Joined as {}", name);
+                client.name = Some(name);
+            },
+            Message::Welcome { version, .. } | Message::Reject { version } => {
+                warn!("The host speaks protocol version {} rather than {}, so can't be \
+                       joined", version, PROTOCOL_VERSION);
+                cmds.remove_resource::<Client>();
+                return;
+            },
+            Message::Start { game, question_count, token_count, scoring, time_limit,
+                             time_bonus } => {
+                client.pending = Some(PendingGame {
+                    id: game,
+                    question_count,
+                    token_count,
+                    scoring,
+                    time_limit,
+                    time_bonus,
+                    questions: Vec::with_capacity(question_count),
+                });
+            },
+            Message::Question { game, round, question } => {
+                if let Some(pending) = client.pending.as_mut() {
+                    if pending.id == game && pending.questions.len() == round {
+                        pending.questions.push(question);
+                    }
+                }
+            },
+            Message::Leaderboard { standings } => leaderboard.0 = standings,
+            _ => (),
+        }
+    }
+}
+
+// Begins the host's game once every question has arrived, playing it under the
+// host's settings until it is over
+fn start_game(client: Option<ResMut<Client>>,
+              mut settings: ResMut<GameSettings>,
+              mut saved: ResMut<SavedSettings>,
+              mut appstate: ResMut<State<AppState>>,
+              mut cmds: Commands,
+) {
+    let mut client = match client {
+        Some(client) => client,
+        None => return,
+    };
+    let ready = client.pending.as_ref()
+        .is_some_and(|pending| pending.questions.len() == pending.question_count);
+    if !ready {
+        return;
+    }
+
+    let pending = client.pending.take().unwrap();
+    saved.0 = Some(settings.clone());
+    // The host picked the questions, so none of the player's own choices of them apply
+    settings.category = None;
+    settings.difficulty = None;
+    settings.include_true_false = true;
+    settings.mode = GameMode::Standard;
+    settings.question_count = pending.question_count;
+    settings.token_count = pending.token_count;
+    settings.scoring = SCORING_RULES.into_iter()
+        .find(|rule| rule.name() == pending.scoring)
+        .unwrap_or(&Linear);
+    settings.time_limit = pending.time_limit;
+    settings.time_bonus = pending.time_bonus;
+
+    client.playing = Some((pending.id, 0));
+    cmds.insert_resource(Rounds {
+        round_number: 0,
+        round_max: pending.questions.len(),
+        questions: pending.questions,
+    });
+    appstate.set(AppState::Game).unwrap();
+}
+
+// Sends the host each round's wagers as they are submitted
+fn send_submissions(client: Option<ResMut<Client>>, history: Res<WagerHistory>) {
+    let mut client = match client {
+        Some(client) => client,
+        None => return,
+    };
+    let client = &mut *client;
+    let (game, submitted) = match client.playing.as_mut() {
+        Some(playing) => playing,
+        None => return,
+    };
+
+    for (round, wagers) in history.0.iter().enumerate().skip(*submitted) {
+        client.conn.send(&Message::Submit {
+            game: *game,
+            round,
+            wagers: wagers.wagers.clone(),
+        });
+    }
+    *submitted = history.0.len();
+}
+
+// Gives the player back their own settings, even should the host have gone
+fn finish_game(client: Option<ResMut<Client>>,
+               mut settings: ResMut<GameSettings>,
+               mut saved: ResMut<SavedSettings>,
+) {
+    if let Some(mut client) = client {
+        client.playing = None;
+    }
+    if let Some(own) = saved.0.take() {
+        *settings = own;
+    }
+}
+
+// Spawns a line along the bottom of the menu saying who the client is waiting on
+fn spawn_status(asset_server: Res<AssetServer>, mut cmds: Commands) {
+    cmds.spawn_bundle(TextBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: Rect {
+                bottom: Val::Px(10.),
+                left: Val::Px(10.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        text: Text::with_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                font_size: 20.,
+                color: Color::BLACK,
+            },
+            Default::default()
+        ),
+        ..Default::default()
+    }).insert(StatusText);
+}
+
+// Keeps the StatusText in step with the connection to the host
+fn update_status(mut status_query: Query<&mut Text, With<StatusText>>,
+                 client: Option<Res<Client>>,
+) {
+    let status = match client.as_ref() {
+        Some(client) => match &client.name {
+            Some(name) => format!("Joined as {}, waiting for the host to start a game",
+                                  name),
+            None => String::from("Joining host. . ."),
+        },
+        None => String::from("Lost connection to host"),
+    };
+
+    for mut text in status_query.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+fn teardown_status(status_query: Query<Entity, With<StatusText>>, mut cmds: Commands) {
+    for status_id in status_query.iter() {
+        cmds.entity(status_id).despawn_recursive();
+    }
+}
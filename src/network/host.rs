@@ -0,0 +1,538 @@
+use bevy::prelude::*;
+
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+use crate::setup::{GameMode, GameSettings};
+use crate::game::answer::{self, WagerHistory};
+use crate::game::load::{Question, Rounds};
+use crate::network::Leaderboard;
+use crate::network::protocol::{Connection, Message, Standing, PROTOCOL_VERSION};
+
+// Time a client spends between rounds animating the correct answer, with a little
+// over for the network, which isn't held against it
+const ROUND_GRACE: Duration = Duration::from_secs(4);
+
+// Clients connected to this host, along with the game they are being sent
+struct Host {
+    listener: TcpListener,
+    peers: Vec<Peer>,
+    joined: usize,
+    own: Standing,
+    live: Option<LiveGame>,
+}
+
+struct Peer {
+    conn: Connection,
+    // None until the client has sent Join
+    standing: Option<Standing>,
+    answered: Vec<bool>,
+    // When the client's current round began, timed by the host so that no client
+    // can claim a time bonus it didn't earn. That's when it was sent the game, or
+    // when its last round was scored
+    round_started: Instant,
+    // Set once the client has been turned away, to be dropped when told so
+    rejected: bool,
+}
+
+// The game most recently broadcast, which clients may still be playing after the
+// host has moved on to other screens
+struct LiveGame {
+    id: u32,
+    settings: GameSettings,
+    questions: Vec<Question>,
+}
+
+// Listens for clients on port, giving up on hosting should that fail
+pub fn build(app: &mut App, port: u16) {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .and_then(|listener| listener.set_nonblocking(true).map(|_| listener));
+
+    match listener {
+        Ok(listener) => {
+            info!("Hosting LAN games on port {}", port);
+            app.insert_resource(Host {
+                   listener,
+                   peers: Vec::new(),
+                   joined: 0,
+                   own: Standing {
+                       name: String::from("Player 1"),
+                       score: 0,
+                       answered: 0,
+                   },
+                   live: None,
+               })
+               .add_system(accept_peers)
+               .add_system(receive_messages)
+               .add_system(flush_peers)
+               .add_system_set(
+                   SystemSet::on_enter(AppState::Game).with_system(broadcast_game))
+               .add_system_set(
+                   SystemSet::on_update(AppState::Game).with_system(track_host_score));
+        },
+        Err(error) => warn!("Couldn't host on port {}: {}", port, error),
+    }
+}
+
+impl LiveGame {
+    // Start, followed by every Question, as sent to each client
+    fn messages(&self) -> Vec<Message> {
+        let start = Message::Start {
+            game: self.id,
+            question_count: self.questions.len(),
+            token_count: self.settings.token_count,
+            scoring: String::from(self.settings.scoring.name()),
+            time_limit: self.settings.time_limit,
+            time_bonus: self.settings.time_bonus,
+        };
+
+        std::iter::once(start)
+            .chain(self.questions.iter().enumerate().map(|(round, question)| {
+                Message::Question {
+                    game: self.id,
+                    round,
+                    question: question.clone(),
+                }
+            }))
+            .collect()
+    }
+}
+
+impl Host {
+    // Every joined player, best score first
+    fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = std::iter::once(self.own.clone())
+            .chain(self.peers.iter().filter_map(|peer| peer.standing.clone()))
+            .collect();
+        standings.sort_by_key(|standing| std::cmp::Reverse(standing.score));
+
+        standings
+    }
+
+    // Sends the standings to every joined client, and shows them locally
+    fn broadcast_standings(&mut self, leaderboard: &mut Leaderboard) {
+        let standings = self.standings();
+        let message = Message::Leaderboard {
+            standings: standings.clone(),
+        };
+        for peer in self.peers.iter_mut().filter(|peer| peer.standing.is_some()) {
+            peer.conn.send(&message);
+        }
+
+        leaderboard.0 = standings;
+    }
+}
+
+// Takes on any clients waiting to connect
+fn accept_peers(mut host: ResMut<Host>) {
+    loop {
+        match host.listener.accept() {
+            Ok((stream, addr)) => match Connection::new(stream) {
+                Ok(conn) => {
+                    info!("Client connected from {}", addr);
+                    host.peers.push(Peer {
+                        conn,
+                        standing: None,
+                        answered: Vec::new(),
+                        round_started: Instant::now(),
+                        rejected: false,
+                    });
+                },
+                Err(error) => warn!("Couldn't set up connection to {}: {}", addr, error),
+            },
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) => {
+                warn!("Couldn't accept client: {}", error);
+                break;
+            },
+        }
+    }
+}
+
+// Handles every client's messages, dropping any client whose connection has closed
+fn receive_messages(mut host: ResMut<Host>, mut leaderboard: ResMut<Leaderboard>) {
+    let host = &mut *host;
+    let mut changed = false;
+
+    let mut i = 0;
+    while i < host.peers.len() {
+        let messages = match host.peers[i].conn.receive() {
+            Ok(messages) => messages,
+            Err(error) => {
+                info!("Client {} left: {}", describe(&host.peers[i]), error);
+                changed |= host.peers.remove(i).standing.is_some();
+                continue;
+            },
+        };
+
+        for message in messages {
+            changed |= handle_message(&mut host.peers[i], &mut host.joined,
+                                      host.live.as_ref(), message);
+        }
+        i += 1;
+    }
+
+    if changed {
+        host.broadcast_standings(&mut leaderboard);
+    }
+}
+
+// Acts on a single message from peer, returning whether the standings changed
+fn handle_message(peer: &mut Peer,
+                  joined: &mut usize,
+                  live: Option<&LiveGame>,
+                  message: Message,
+) -> bool {
+    if peer.rejected {
+        return false;
+    }
+
+    match message {
+        Message::Join { version, .. } if version != PROTOCOL_VERSION => {
+            warn!("Turning away {}, which speaks protocol version {} rather than {}",
+                  describe(peer), version, PROTOCOL_VERSION);
+            peer.conn.send(&Message::Reject {
+                version: PROTOCOL_VERSION,
+            });
+            peer.rejected = true;
+
+            false
+        },
+        Message::Join { name, .. } if peer.standing.is_none() => {
+            // The host is always Player 1
+            *joined += 1;
+            let name = name.filter(|name| !name.trim().is_empty())
+                           .unwrap_or_else(|| format!("Player {}", *joined + 1));
+            peer.conn.send(&Message::Welcome {
+                version: PROTOCOL_VERSION,
+                name: name.clone(),
+            });
+            peer.standing = Some(Standing {
+                name,
+                score: 0,
+                answered: 0,
+            });
+
+            // Latecomers are sent the game already underway
+            if let Some(live) = live {
+                peer.answered = vec![false; live.questions.len()];
+                peer.round_started = Instant::now();
+                for message in live.messages() {
+                    peer.conn.send(&message);
+                }
+            }
+
+            true
+        },
+        Message::Submit { game, round, wagers } => {
+            let (live, standing) = match (live, peer.standing.as_mut()) {
+                (Some(live), Some(standing)) if live.id == game => (live, standing),
+                _ => return false,
+            };
+
+            // Only the first submission of each round counts, and only if it could
+            // have been made on the board
+            let question = match live.questions.get(round) {
+                Some(question) if peer.answered.get(round) == Some(&false) => question,
+                _ => return false,
+            };
+            if wagers.len() != question.answers.len()
+               || wagers.iter().sum::<usize>() > live.settings.token_count {
+                warn!("Ignoring impossible wagers from {}", standing.name);
+                return false;
+            }
+
+            let correct = question.answers.iter()
+                .position(|answer| answer.truth)
+                .unwrap_or_default();
+            let remaining = time_remaining(peer.round_started.elapsed(), &live.settings);
+            peer.answered[round] = true;
+            peer.round_started = Instant::now();
+            standing.score += answer::points(&wagers, correct, remaining, &live.settings);
+            standing.answered += 1;
+
+            true
+        },
+        _ => false,
+    }
+}
+
+// Share of the time limit left after elapsed, which is always all of it when untimed
+fn time_remaining(elapsed: Duration, settings: &GameSettings) -> f32 {
+    let elapsed = elapsed.saturating_sub(ROUND_GRACE).as_secs_f32();
+    settings.time_limit.map_or(1., |seconds| (1. - elapsed / seconds as f32).max(0.))
+}
+
+fn describe(peer: &Peer) -> String {
+    match (&peer.standing, peer.conn.peer_addr()) {
+        (Some(standing), _) => standing.name.clone(),
+        (None, Ok(addr)) => addr.to_string(),
+        (None, Err(_)) => String::from("(unknown)"),
+    }
+}
+
+// Writes out whatever has been queued for each client, disconnecting any turned away
+// once they have been told
+fn flush_peers(mut host: ResMut<Host>, mut leaderboard: ResMut<Leaderboard>) {
+    let mut changed = false;
+    let mut i = 0;
+    while i < host.peers.len() {
+        let peer = &mut host.peers[i];
+        match peer.conn.flush() {
+            Err(error) => {
+                info!("Client {} left: {}", describe(peer), error);
+                changed |= host.peers.remove(i).standing.is_some();
+            },
+            Ok(()) if peer.rejected && peer.conn.is_flushed() => {
+                host.peers.remove(i);
+            },
+            Ok(()) => i += 1,
+        }
+    }
+
+    if changed {
+        host.broadcast_standings(&mut leaderboard);
+    }
+}
+
+// Sends every joined client the game the host has just begun. Only games with a
+// fixed set of questions can be shared
+fn broadcast_game(mut host: ResMut<Host>,
+                  mut leaderboard: ResMut<Leaderboard>,
+                  rounds: Res<Rounds>,
+                  settings: Res<GameSettings>,
+) {
+    if !matches!(settings.mode, GameMode::Standard | GameMode::Daily) {
+        info!("{} games aren't shared with LAN clients", settings.mode);
+        return;
+    }
+
+    let host = &mut *host;
+    let live = LiveGame {
+        id: host.live.as_ref().map_or(0, |live| live.id + 1),
+        settings: settings.clone(),
+        questions: rounds.questions.clone(),
+    };
+
+    host.own.score = 0;
+    host.own.answered = 0;
+    let messages = live.messages();
+    for peer in host.peers.iter_mut() {
+        if let Some(standing) = peer.standing.as_mut() {
+            standing.score = 0;
+            standing.answered = 0;
+            peer.answered = vec![false; live.questions.len()];
+            peer.round_started = Instant::now();
+            for message in messages.iter() {
+                peer.conn.send(message);
+            }
+        }
+    }
+
+    host.live = Some(live);
+    host.broadcast_standings(&mut leaderboard);
+}
+
+// Puts the host's own score on the leaderboard as each round is submitted
+fn track_host_score(mut host: ResMut<Host>,
+                    mut leaderboard: ResMut<Leaderboard>,
+                    history: Res<WagerHistory>,
+                    settings: Res<GameSettings>,
+) {
+    if !history.is_changed() || !matches!(settings.mode, GameMode::Standard | GameMode::Daily) {
+        return;
+    }
+
+    host.own.score = history.0.iter().map(|round| round.points).sum();
+    host.own.answered = history.0.len();
+    host.broadcast_standings(&mut leaderboard);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::load::Answer;
+    use crate::network::protocol::tests::{pair, receive_some};
+
+    // A joined peer, along with the client's end of its connection
+    fn peer() -> (Peer, Connection) {
+        let (client, conn) = pair();
+        let peer = Peer {
+            conn,
+            standing: None,
+            answered: Vec::new(),
+            round_started: Instant::now(),
+            rejected: false,
+        };
+
+        (peer, client)
+    }
+
+    // A live game of two questions, each with the correct answer first
+    fn live_game() -> LiveGame {
+        let question = Question {
+            text: String::from("Is this a question?"),
+            category: String::from("General Knowledge"),
+            difficulty: String::from("easy"),
+            answers: vec![
+                Answer { text: String::from("Yes"), truth: true },
+                Answer { text: String::from("No"), truth: false },
+                Answer { text: String::from("Maybe"), truth: false },
+            ],
+        };
+
+        LiveGame {
+            id: 4,
+            settings: GameSettings::default(),
+            questions: vec![question.clone(), question],
+        }
+    }
+
+    fn join(peer: &mut Peer, joined: &mut usize, live: Option<&LiveGame>) -> bool {
+        handle_message(peer, joined, live, Message::Join {
+            version: PROTOCOL_VERSION,
+            name: None,
+        })
+    }
+
+    fn submit(peer: &mut Peer, live: &LiveGame, round: usize, wagers: Vec<usize>) -> bool {
+        handle_message(peer, &mut 1, Some(live), Message::Submit {
+            game: live.id,
+            round,
+            wagers,
+        })
+    }
+
+    fn send_all(peer: &mut Peer, client: &mut Connection, count: usize) -> Vec<Message> {
+        peer.conn.flush().unwrap();
+        receive_some(client, count).unwrap()
+    }
+
+    #[test]
+    fn joining_names_the_player() {
+        let (mut peer, mut client) = peer();
+        let mut joined = 0;
+        assert!(join(&mut peer, &mut joined, None));
+
+        assert_eq!(joined, 1);
+        assert_eq!(peer.standing.as_ref().unwrap().name, "Player 2");
+        let messages = send_all(&mut peer, &mut client, 1);
+        assert!(matches!(&messages[..], [Message::Welcome { name, .. }] if name == "Player 2"));
+
+        // Joining again changes nothing
+        assert!(!join(&mut peer, &mut joined, None));
+        assert_eq!(joined, 1);
+    }
+
+    #[test]
+    fn latecomers_are_sent_the_live_game() {
+        let (mut peer, mut client) = peer();
+        let live = live_game();
+        assert!(join(&mut peer, &mut 0, Some(&live)));
+
+        assert_eq!(peer.answered, [false, false]);
+        let messages = send_all(&mut peer, &mut client, 4);
+        assert!(matches!(&messages[..], [
+            Message::Welcome { .. },
+            Message::Start { game: 4, question_count: 2, .. },
+            Message::Question { game: 4, round: 0, .. },
+            Message::Question { game: 4, round: 1, .. },
+        ]));
+    }
+
+    #[test]
+    fn mismatched_versions_are_rejected() {
+        let (mut peer, mut client) = peer();
+        let changed = handle_message(&mut peer, &mut 0, None, Message::Join {
+            version: PROTOCOL_VERSION + 1,
+            name: Some(String::from("Future")),
+        });
+
+        assert!(!changed);
+        assert!(peer.rejected && peer.standing.is_none());
+        let messages = send_all(&mut peer, &mut client, 1);
+        assert!(matches!(&messages[..], [Message::Reject { version: PROTOCOL_VERSION }]));
+
+        // Nothing more is heard from a rejected client
+        assert!(!join(&mut peer, &mut 0, None));
+    }
+
+    #[test]
+    fn submissions_are_scored_once() {
+        let (mut peer, _client) = peer();
+        let live = live_game();
+        join(&mut peer, &mut 0, Some(&live));
+
+        assert!(submit(&mut peer, &live, 0, vec![3, 2, 0]));
+        assert!(!submit(&mut peer, &live, 0, vec![5, 0, 0]));
+
+        let standing = peer.standing.as_ref().unwrap();
+        assert_eq!(standing.score, 3);
+        assert_eq!(standing.answered, 1);
+        assert_eq!(peer.answered, [true, false]);
+    }
+
+    #[test]
+    fn time_bonus_is_timed_by_the_host() {
+        let (mut peer, _client) = peer();
+        let mut live = live_game();
+        live.settings.time_limit = Some(10);
+        live.settings.time_bonus = true;
+        join(&mut peer, &mut 0, Some(&live));
+
+        // Submitted at once, earning the whole bonus of half as much again
+        assert!(submit(&mut peer, &live, 0, vec![3, 2, 0]));
+        assert_eq!(peer.standing.as_ref().unwrap().score, 3 + 3);
+
+        // Submitted halfway through the time limit, earning half the bonus
+        peer.round_started = Instant::now() - ROUND_GRACE - Duration::from_secs(5);
+        assert!(submit(&mut peer, &live, 1, vec![3, 2, 0]));
+        assert_eq!(peer.standing.as_ref().unwrap().score, 6 + 3 + 1);
+    }
+
+    #[test]
+    fn late_submissions_earn_no_time_bonus() {
+        let mut settings = GameSettings {
+            time_limit: Some(10),
+            ..Default::default()
+        };
+
+        assert_eq!(time_remaining(ROUND_GRACE, &settings), 1.);
+        assert_eq!(time_remaining(ROUND_GRACE + Duration::from_secs(30), &settings), 0.);
+        settings.time_limit = None;
+        assert_eq!(time_remaining(ROUND_GRACE + Duration::from_secs(30), &settings), 1.);
+    }
+
+    #[test]
+    fn impossible_submissions_are_ignored() {
+        let (mut peer, _client) = peer();
+        let live = live_game();
+        join(&mut peer, &mut 0, Some(&live));
+
+        // Too many tokens, the wrong number of answers, and a round which doesn't exist
+        assert!(!submit(&mut peer, &live, 0, vec![5, 5, 5]));
+        assert!(!submit(&mut peer, &live, 0, vec![5, 0]));
+        assert!(!submit(&mut peer, &live, 2, vec![5, 0, 0]));
+        // Another game entirely
+        assert!(!handle_message(&mut peer, &mut 1, Some(&live), Message::Submit {
+            game: live.id + 1,
+            round: 0,
+            wagers: vec![5, 0, 0],
+        }));
+
+        assert_eq!(peer.standing.as_ref().unwrap().score, 0);
+        assert_eq!(peer.answered, [false, false]);
+    }
+
+    #[test]
+    fn submissions_need_a_join() {
+        let (mut peer, _client) = peer();
+        let live = live_game();
+
+        assert!(!submit(&mut peer, &live, 0, vec![5, 0, 0]));
+        assert!(peer.standing.is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::AppState;
+use crate::network::protocol::{Standing, DEFAULT_PORT};
+
+pub mod protocol;
+mod host;
+mod client;
+
+pub struct NetworkPlugin;
+
+// How this instance takes part in LAN games, chosen on the command line:
+//   --host [PORT]          host games for others on the local network
+//   --join ADDR[:PORT]     play the games hosted at ADDR
+//   --name NAME            the name to join under
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum NetworkRole {
+    Offline,
+    Host(u16),
+    Client {
+        addr: String,
+        name: Option<String>,
+    },
+}
+
+// The standings in the current LAN game, as last sent by the host
+#[derive(Default)]
+pub struct Leaderboard(pub Vec<Standing>);
+
+#[derive(Component)]
+struct LeaderboardText;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        let role = NetworkRole::from_args(std::env::args().skip(1));
+        match &role {
+            NetworkRole::Offline => (),
+            NetworkRole::Host(port) => host::build(app, *port),
+            NetworkRole::Client { addr, name } => client::build(app, addr, name.clone()),
+        }
+
+        app.insert_resource(role)
+           .init_resource::<Leaderboard>()
+           .add_system_set(
+               SystemSet::on_enter(AppState::Game).with_system(spawn_leaderboard))
+           .add_system_set(
+               SystemSet::on_update(AppState::Game).with_system(update_leaderboard))
+           .add_system_set(
+               SystemSet::on_exit(AppState::Game).with_system(teardown_leaderboard));
+    }
+}
+
+impl NetworkRole {
+    pub fn from_args(args: impl Iterator<Item = String>) -> NetworkRole {
+        let mut role = NetworkRole::Offline;
+        let mut name = None;
+
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host" => {
+                    let port = args.peek().and_then(|port| port.parse().ok());
+                    if port.is_some() {
+                        args.next();
+                    }
+                    role = NetworkRole::Host(port.unwrap_or(DEFAULT_PORT));
+                },
+                "--join" => match args.next() {
+                    Some(addr) if addr.contains(':') => {
+                        role = NetworkRole::Client { addr, name: None };
+                    },
+                    Some(addr) => {
+                        role = NetworkRole::Client {
+                            addr: format!("{}:{}", addr, DEFAULT_PORT),
+                            name: None,
+                        };
+                    },
+                    None => warn!("--join needs the address of a host"),
+                },
+                "--name" => name = args.next(),
+                _ => warn!("Ignoring unknown argument {}", arg),
+            }
+        }
+
+        if let NetworkRole::Client { name: client_name, .. } = &mut role {
+            *client_name = name;
+        }
+
+        role
+    }
+}
+
+// Spawns the leaderboard in the top right of the board during LAN games
+fn spawn_leaderboard(role: Res<NetworkRole>,
+                     asset_server: Res<AssetServer>,
+                     mut cmds: Commands,
+) {
+    if *role == NetworkRole::Offline {
+        return;
+    }
+
+    cmds.spawn_bundle(TextBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: Rect {
+                top: Val::Px(10.),
+                right: Val::Px(10.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        text: Text::with_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+                font_size: 16.,
+                color: Color::BLACK,
+            },
+            Default::default()
+        ),
+        ..Default::default()
+    }).insert(LeaderboardText);
+}
+
+// Keeps the LeaderboardText in sync with the Leaderboard
+fn update_leaderboard(mut text_query: Query<(&mut Text, ChangeTrackers<LeaderboardText>)>,
+                      leaderboard: Res<Leaderboard>,
+) {
+    for (mut text, tracker) in text_query.iter_mut() {
+        if leaderboard.is_changed() || tracker.is_added() {
+            let standings: Vec<String> = leaderboard.0.iter()
+                .enumerate()
+                .map(|(place, standing)| format!("{}. {}: {}", place + 1, standing.name,
+                                                 standing.score))
+                .collect();
+            text.sections[0].value = format!("Leaderboard\n{}", standings.join("\n"));
+        }
+    }
+}
+
+fn teardown_leaderboard(text_query: Query<Entity, With<LeaderboardText>>,
+                        mut cmds: Commands,
+) {
+    for text_id in text_query.iter() {
+        cmds.entity(text_id).despawn_recursive();
+    }
+}
@@ -0,0 +1,294 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use crate::game::load::Question;
+
+// Port a host listens on unless told otherwise
+pub const DEFAULT_PORT: u16 = 7878;
+// Raised whenever Message changes shape, so that host and client can tell they won't
+// understand one another
+pub const PROTOCOL_VERSION: u32 = 1;
+// Longest line accepted before a connection is judged to be misbehaving
+const MAX_LINE: usize = 1 << 20;
+
+// Everything sent between host and clients, as one JSON object per line tagged by
+// its "type". Clients send Join and Submit, and the host sends the rest
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    // A client introducing itself, optionally by name. Clients from before versions
+    // were sent count as version 0
+    Join {
+        #[serde(default)]
+        version: u32,
+        name: Option<String>,
+    },
+    // The name the host has given a client on the leaderboard
+    Welcome {
+        #[serde(default)]
+        version: u32,
+        name: String,
+    },
+    // Turns away a client speaking another version, just before disconnecting it
+    Reject {
+        version: u32,
+    },
+    // A new game, whose questions follow one per Question message
+    Start {
+        game: u32,
+        question_count: usize,
+        token_count: usize,
+        scoring: String,
+        time_limit: Option<u32>,
+        time_bonus: bool,
+    },
+    Question {
+        game: u32,
+        round: usize,
+        question: Question,
+    },
+    // A client's wagers on one round. The host times each round itself, so any
+    // time bonus doesn't rest on the client's word
+    Submit {
+        game: u32,
+        round: usize,
+        wagers: Vec<usize>,
+    },
+    // Every player's score, best first
+    Leaderboard {
+        standings: Vec<Standing>,
+    },
+}
+
+// A player's place on the leaderboard
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Standing {
+    pub name: String,
+    pub score: i32,
+    pub answered: usize,
+}
+
+// A TCP stream which never blocks, buffering partial lines in either direction
+pub struct Connection {
+    stream: TcpStream,
+    inbox: Vec<u8>,
+    outbox: Vec<u8>,
+    // Whether the other end has closed, leaving only what is already in the inbox
+    closed: bool,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Connection> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Connection {
+            stream,
+            inbox: Vec::new(),
+            outbox: Vec::new(),
+            closed: false,
+        })
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    // Queues message to be written by the next flush
+    pub fn send(&mut self, message: &Message) {
+        match serde_json::to_vec(message) {
+            Ok(line) => {
+                self.outbox.extend(line);
+                self.outbox.push(b'\n');
+            },
+            Err(error) => warn!("Couldn't encode message: {}", error),
+        }
+    }
+
+    // Whether everything queued has been written
+    pub fn is_flushed(&self) -> bool {
+        self.outbox.is_empty()
+    }
+
+    // Writes as much of the queue as the socket will currently take
+    pub fn flush(&mut self) -> io::Result<()> {
+        while !self.outbox.is_empty() {
+            match self.stream.write(&self.outbox) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.outbox.drain(..written);
+                },
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Every complete message which has arrived since the last call. Lines which
+    // can't be understood are skipped. Once the other end has closed, and every
+    // message it sent before doing so has been returned, this fails
+    pub fn receive(&mut self) -> io::Result<Vec<Message>> {
+        let mut buf = [0; 4096];
+        while !self.closed {
+            match self.stream.read(&mut buf) {
+                Ok(0) => self.closed = true,
+                Ok(read) => self.inbox.extend_from_slice(&buf[..read]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.inbox.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.inbox.drain(..=end).collect();
+            match serde_json::from_slice(&line) {
+                Ok(message) => messages.push(message),
+                Err(error) => warn!("Skipping malformed message: {}", error),
+            }
+        }
+        if self.inbox.len() > MAX_LINE {
+            return Err(io::Error::new(ErrorKind::InvalidData, "message too long"));
+        }
+        if self.closed && messages.is_empty() {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    // A Connection over loopback, along with the raw stream at its other end
+    pub fn raw_pair() -> (TcpStream, Connection) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        (client, Connection::new(server).unwrap())
+    }
+
+    // Two Connections at either end of a loopback socket
+    pub fn pair() -> (Connection, Connection) {
+        let (client, server) = raw_pair();
+        (Connection::new(client).unwrap(), server)
+    }
+
+    // Receives on conn until at least count messages have arrived, the connection
+    // fails, or a second has passed
+    pub fn receive_some(conn: &mut Connection, count: usize) -> io::Result<Vec<Message>> {
+        let start = Instant::now();
+        let mut messages = Vec::new();
+        while messages.len() < count && start.elapsed() < Duration::from_secs(1) {
+            messages.extend(conn.receive()?);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        Ok(messages)
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let (mut client, mut host) = pair();
+        client.send(&Message::Join {
+            version: PROTOCOL_VERSION,
+            name: Some(String::from("Ada")),
+        });
+        client.send(&Message::Submit {
+            game: 3,
+            round: 1,
+            wagers: vec![0, 4, 1],
+        });
+        client.flush().unwrap();
+
+        let messages = receive_some(&mut host, 2).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(&messages[0],
+                         Message::Join { version: PROTOCOL_VERSION, name: Some(name) }
+                             if name == "Ada"));
+        assert!(matches!(&messages[1],
+                         Message::Submit { game: 3, round: 1, wagers }
+                             if *wagers == [0, 4, 1]));
+    }
+
+    #[test]
+    fn partial_lines_wait_for_the_rest() {
+        let (mut raw, mut conn) = raw_pair();
+        raw.write_all(br#"{"type":"welcome","version":1,"#).unwrap();
+        assert!(receive_some(&mut conn, 1).unwrap().is_empty());
+
+        raw.write_all(b"\"name\":\"Player 2\"}\n").unwrap();
+        let messages = receive_some(&mut conn, 1).unwrap();
+        assert!(matches!(&messages[..], [Message::Welcome { version: 1, name }]
+                             if name == "Player 2"));
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let (mut raw, mut conn) = raw_pair();
+        raw.write_all(b"not json\n{\"type\":\"unknown\"}\n{\"type\":\"reject\",\"version\":9}\n")
+           .unwrap();
+
+        let messages = receive_some(&mut conn, 1).unwrap();
+        assert!(matches!(&messages[..], [Message::Reject { version: 9 }]));
+    }
+
+    #[test]
+    fn join_without_a_version_is_version_0() {
+        let (mut raw, mut conn) = raw_pair();
+        raw.write_all(b"{\"type\":\"join\",\"name\":null}\n").unwrap();
+
+        let messages = receive_some(&mut conn, 1).unwrap();
+        assert!(matches!(&messages[..], [Message::Join { version: 0, name: None }]));
+    }
+
+    #[test]
+    fn overlong_lines_fail() {
+        let (mut raw, mut conn) = raw_pair();
+        let writer = std::thread::spawn(move || {
+            // The reader may give up before the whole line is written
+            let _ = raw.write_all(&vec![b'x'; MAX_LINE + 1]);
+        });
+
+        let error = loop {
+            match conn.receive() {
+                Ok(messages) => assert!(messages.is_empty()),
+                Err(error) => break error,
+            }
+        };
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn messages_sent_before_closing_still_arrive() {
+        let (mut client, mut host) = pair();
+        host.send(&Message::Reject {
+            version: PROTOCOL_VERSION,
+        });
+        host.flush().unwrap();
+        drop(host);
+
+        let messages = receive_some(&mut client, 1).unwrap();
+        assert!(matches!(&messages[..], [Message::Reject { .. }]));
+        let error = loop {
+            match client.receive() {
+                Ok(messages) => assert!(messages.is_empty()),
+                Err(error) => break error,
+            }
+        };
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    }
+}
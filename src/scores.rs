@@ -0,0 +1,517 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::setup::{cycle, GameMode, GameSettings};
+use crate::storage::{civil_date, read_json, unix_time, write_json};
+
+const SCORES_FILE: &str = "high_scores.json";
+// Number of scores listed at once
+const TOP_N: usize = 10;
+
+pub struct ScoresPlugin;
+
+// A completed game, along with the settings it was played under
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: i32,
+    pub max_score: i32,
+    pub played_at: u64,
+    pub question_count: usize,
+    pub token_count: usize,
+    pub scoring: String,
+    pub category: Option<String>,
+    pub difficulty: Option<String>,
+    // Scores saved before modes and timers were recorded were all untimed Standard
+    // games
+    #[serde(default = "standard_mode")]
+    pub mode: String,
+    #[serde(default)]
+    pub time_limit: Option<u32>,
+    #[serde(default)]
+    pub time_bonus: bool,
+}
+
+// Every score in the save file, loaded when the screen is entered
+#[derive(Default)]
+struct SavedScores(Vec<HighScore>);
+
+// Settings the listed scores must have been played under, where None allows any
+#[derive(Default)]
+struct ScoreFilters {
+    question_count: Option<usize>,
+    token_count: Option<usize>,
+    scoring: Option<String>,
+    category: Option<String>,
+    difficulty: Option<String>,
+    mode: Option<String>,
+    timing: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Filter {
+    Mode,
+    Timing,
+    Questions,
+    Tokens,
+    Scoring,
+    Category,
+    Difficulty,
+}
+
+#[derive(Component)]
+struct ScoresElem;
+#[derive(Component)]
+struct FilterValue(Filter);
+#[derive(Component)]
+struct ScoreTable;
+#[derive(Component, Clone, Copy)]
+enum ScoresButton {
+    Cycle(Filter, i32),
+    Back,
+}
+
+impl Plugin for ScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SavedScores::default())
+           .insert_resource(ScoreFilters::default())
+           .add_system_set(
+               SystemSet::on_enter(AppState::HighScores).with_system(load_scores)
+                                                        .with_system(spawn_scores))
+           .add_system_set(
+               SystemSet::on_update(AppState::HighScores).with_system(scores_buttons)
+                                                         .with_system(update_scores))
+           .add_system_set(
+               SystemSet::on_exit(AppState::HighScores).with_system(teardown_scores));
+    }
+}
+
+impl HighScore {
+    // Builds a HighScore for a game of question_count questions just completed under
+    // settings
+    pub fn new(score: i32,
+               max_score: i32,
+               question_count: usize,
+               settings: &GameSettings,
+    ) -> HighScore {
+        // The daily challenge draws its questions regardless of category or difficulty
+        let (category, difficulty) = if settings.mode == GameMode::Daily {
+            (None, None)
+        } else {
+            (settings.category.as_ref().map(|category| category.name.clone()),
+             settings.difficulty.map(|difficulty| difficulty.to_string()))
+        };
+
+        HighScore {
+            score,
+            max_score,
+            played_at: unix_time(),
+            question_count,
+            token_count: settings.token_count,
+            scoring: String::from(settings.scoring.name()),
+            category,
+            difficulty,
+            mode: settings.mode.to_string(),
+            time_limit: settings.time_limit,
+            time_bonus: settings.time_limit.is_some() && settings.time_bonus,
+        }
+    }
+
+    // How the game was timed, as listed and filtered on
+    fn timing(&self) -> String {
+        match self.time_limit {
+            None => String::from("Untimed"),
+            Some(limit) if self.time_bonus => format!("{}s with Bonus", limit),
+            Some(limit) => format!("{}s", limit),
+        }
+    }
+}
+
+fn standard_mode() -> String {
+    GameMode::Standard.to_string()
+}
+
+impl ScoreFilters {
+    // Whether high_score was played under every setting being filtered for
+    fn allows(&self, high_score: &HighScore) -> bool {
+        self.mode.as_ref().map_or(true, |mode| *mode == high_score.mode)
+            && self.timing.as_ref().map_or(true, |timing| *timing == high_score.timing())
+            && self.question_count.map_or(true, |count| count == high_score.question_count)
+            && self.token_count.map_or(true, |count| count == high_score.token_count)
+            && self.scoring.as_ref().map_or(true, |scoring| *scoring == high_score.scoring)
+            && self.category.as_ref()
+                   .map_or(true, |category| Some(category) == high_score.category.as_ref())
+            && self.difficulty.as_ref()
+                   .map_or(true, |difficulty| {
+                       Some(difficulty) == high_score.difficulty.as_ref()
+                   })
+    }
+}
+
+// Adds a completed game to the save file
+pub fn record(high_score: HighScore) {
+    let mut scores: Vec<HighScore> = read_json(SCORES_FILE);
+    scores.push(high_score);
+    write_json(SCORES_FILE, &scores);
+}
+
+// Reads the save file afresh, so the table includes any games just completed
+fn load_scores(mut saved: ResMut<SavedScores>) {
+    saved.0 = read_json(SCORES_FILE);
+}
+
+// Spawns a row for every Filter above the table of scores, along with a back button
+fn spawn_scores(asset_server: Res<AssetServer>, mut cmds: Commands) {
+    let font = asset_server.load("fonts/PublicSans-Medium.ttf");
+
+    cmds.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::ColumnReverse,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        // Title Text
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: Rect::all(Val::Px(10.)),
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "High Scores",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 50.,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+
+        for (filter, label) in [(Filter::Mode, "Mode"),
+                                (Filter::Timing, "Timer"),
+                                (Filter::Questions, "Questions"),
+                                (Filter::Tokens, "Tokens"),
+                                (Filter::Scoring, "Scoring"),
+                                (Filter::Category, "Category"),
+                                (Filter::Difficulty, "Difficulty")] {
+            spawn_filter_row(parent, font.clone(), filter, label);
+        }
+
+        // Score Table
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(95.), Val::Px(220.)),
+                margin: Rect::all(Val::Px(10.)),
+                flex_direction: FlexDirection::ColumnReverse,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    max_size: Size::new(Val::Px(760.), Val::Undefined),
+                    ..Default::default()
+                },
+                text: Text::default(),
+                ..Default::default()
+            }).insert(ScoreTable);
+        });
+
+        // Back Button
+        parent.spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(150.), Val::Px(50.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Back",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        }).insert(ScoresButton::Back);
+    }).insert(ScoresElem);
+}
+
+// Spawns a row holding a Filter's label, its current value, and arrows to change it
+fn spawn_filter_row(parent: &mut ChildBuilder,
+                    font: Handle<Font>,
+                    filter: Filter,
+                    label: &str,
+) {
+    parent.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Px(32.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        // Filter Label
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(150.), Val::Percent(100.)),
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 26.,
+                        color: Color::BLACK,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+
+        spawn_arrow_button(parent, font.clone(), "<", ScoresButton::Cycle(filter, -1));
+
+        // Filter Value
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(420.), Val::Percent(100.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 22.,
+                        color: Color::BLACK,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            }).insert(FilterValue(filter));
+        });
+
+        spawn_arrow_button(parent, font, ">", ScoresButton::Cycle(filter, 1));
+    });
+}
+
+// Spawns a small arrow button which steps a Filter through its options
+fn spawn_arrow_button(parent: &mut ChildBuilder,
+                      font: Handle<Font>,
+                      arrow: &str,
+                      button: ScoresButton,
+) {
+    parent.spawn_bundle(ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(30.), Val::Px(30.)),
+            margin: Rect {
+                left: Val::Px(2.),
+                right: Val::Px(2.),
+                ..Default::default()
+            },
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            text: Text::with_section(
+                arrow,
+                TextStyle {
+                    font,
+                    font_size: 26.,
+                    color: Color::rgb(1., 1., 1.),
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+    })
+    .insert(button);
+}
+
+// Click handler for every button on the high score screen
+fn scores_buttons(mut state: ResMut<State<AppState>>,
+                  mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor, &ScoresButton),
+                                   Pressed>,
+                  mut filters: ResMut<ScoreFilters>,
+                  saved: Res<SavedScores>,
+                  button_colors: Res<ButtonMaterials>,
+) {
+    for (interaction, pad_click, mut color, button) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                match *button {
+                    ScoresButton::Cycle(filter, step) => {
+                        cycle_filter(&mut filters, &saved, filter, step);
+                    },
+                    ScoresButton::Back => state.set(AppState::Menu).unwrap(),
+                }
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            }
+        }
+    }
+}
+
+// Steps a Filter through "Any" and every value found among the saved scores
+fn cycle_filter(filters: &mut ScoreFilters,
+                saved: &SavedScores,
+                filter: Filter,
+                step: i32,
+) {
+    match filter {
+        Filter::Mode => {
+            let options = filter_options(saved, |score| Some(score.mode.clone()));
+            filters.mode = cycle(&options, &filters.mode, step);
+        },
+        Filter::Timing => {
+            let options = filter_options(saved, |score| Some(score.timing()));
+            filters.timing = cycle(&options, &filters.timing, step);
+        },
+        Filter::Questions => {
+            let options = filter_options(saved, |score| Some(score.question_count));
+            filters.question_count = cycle(&options, &filters.question_count, step);
+        },
+        Filter::Tokens => {
+            let options = filter_options(saved, |score| Some(score.token_count));
+            filters.token_count = cycle(&options, &filters.token_count, step);
+        },
+        Filter::Scoring => {
+            let options = filter_options(saved, |score| Some(score.scoring.clone()));
+            filters.scoring = cycle(&options, &filters.scoring, step);
+        },
+        Filter::Category => {
+            let options = filter_options(saved, |score| score.category.clone());
+            filters.category = cycle(&options, &filters.category, step);
+        },
+        Filter::Difficulty => {
+            let options = filter_options(saved, |score| score.difficulty.clone());
+            filters.difficulty = cycle(&options, &filters.difficulty, step);
+        },
+    }
+}
+
+// None followed by every distinct value field takes among the saved scores, in order
+fn filter_options<T: Clone + Ord>(saved: &SavedScores,
+                                  field: impl Fn(&HighScore) -> Option<T>,
+) -> Vec<Option<T>> {
+    let mut values: Vec<T> = saved.0.iter().filter_map(field).collect();
+    values.sort();
+    values.dedup();
+
+    std::iter::once(None).chain(values.into_iter().map(Some)).collect()
+}
+
+// Keeps the displayed filters and the table of top scores in sync with ScoreFilters
+fn update_scores(mut value_query: Query<(&mut Text, &FilterValue,
+                                         ChangeTrackers<FilterValue>)>,
+                 mut table_query: Query<(&mut Text, ChangeTrackers<ScoreTable>),
+                                        Without<FilterValue>>,
+                 asset_server: Res<AssetServer>,
+                 filters: Res<ScoreFilters>,
+                 saved: Res<SavedScores>,
+) {
+    for (mut text, value, tracker) in value_query.iter_mut() {
+        if filters.is_changed() || tracker.is_added() {
+            text.sections[0].value = match value.0 {
+                Filter::Mode => any_or(filters.mode.as_ref()),
+                Filter::Timing => any_or(filters.timing.as_ref()),
+                Filter::Questions => any_or(filters.question_count),
+                Filter::Tokens => any_or(filters.token_count),
+                Filter::Scoring => any_or(filters.scoring.as_ref()),
+                Filter::Category => any_or(filters.category.as_ref()),
+                Filter::Difficulty => any_or(filters.difficulty.as_ref()),
+            };
+        }
+    }
+
+    for (mut text, tracker) in table_query.iter_mut() {
+        if filters.is_changed() || tracker.is_added() {
+            text.sections = score_lines(&filters, &saved, &asset_server);
+        }
+    }
+}
+
+// One line of text for each of the top scores passing the filters
+fn score_lines(filters: &ScoreFilters,
+               saved: &SavedScores,
+               asset_server: &AssetServer,
+) -> Vec<TextSection> {
+    let mut scores: Vec<&HighScore> = saved.0.iter()
+        .filter(|score| filters.allows(score))
+        .collect();
+    scores.sort_by(|a, b| b.score.cmp(&a.score).then(a.played_at.cmp(&b.played_at)));
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/PublicSans-Medium.ttf"),
+        font_size: 16.,
+        color: Color::BLACK,
+    };
+
+    if scores.is_empty() {
+        return vec![TextSection {
+            value: String::from("No games played with these settings yet!"),
+            style,
+        }];
+    }
+
+    scores.iter().take(TOP_N).enumerate().map(|(i, score)| {
+        let (year, month, day) = civil_date(score.played_at);
+        TextSection {
+            value: format!("{:>2}.  {} of {}  on {}-{:02}-{:02}  ({}, {} questions, \
+                            {} tokens, {}, {}, {}, {})\n",
+                           i + 1, score.score, score.max_score, year, month, day,
+                           score.mode, score.question_count, score.token_count,
+                           score.scoring, score.timing(),
+                           score.category.as_deref().unwrap_or("Any Category"),
+                           score.difficulty.as_deref().unwrap_or("Any Difficulty")),
+            style: style.clone(),
+        }
+    }).collect()
+}
+
+// Displays a filter's value, or "Any" when it is not being filtered on
+fn any_or<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_else(|| String::from("Any"))
+}
+
+// Despawns the high score screen
+fn teardown_scores(scores_query: Query<Entity, With<ScoresElem>>, mut cmds: Commands) {
+    for scores_id in scores_query.iter() {
+        cmds.entity(scores_id).despawn_recursive();
+    }
+}
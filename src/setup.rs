@@ -0,0 +1,597 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use async_compat::Compat;
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::game::source::{Category, DailySource, Difficulty, LoadError, Source};
+use crate::game::scoring::{ScoringRule, Linear, SCORING_RULES};
+use crate::game::bot::BotProfile;
+
+pub struct SetupPlugin;
+
+// Choices made on the setup screen which shape the coming game
+#[derive(Clone)]
+pub struct GameSettings {
+    pub category: Option<Category>,
+    pub difficulty: Option<Difficulty>,
+    pub question_count: usize,
+    pub token_count: usize,
+    pub include_true_false: bool,
+    pub scoring: &'static dyn ScoringRule,
+    pub time_limit: Option<u32>,
+    pub time_bonus: bool,
+    pub mode: GameMode,
+    pub players: usize,
+    pub bot: BotProfile,
+    // Percent of questions the bot's favourite answer is right on, or None for it to
+    // know easy questions better than hard ones
+    pub bot_accuracy: Option<u32>,
+}
+
+// Rules for how long a game lasts
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    // A fixed number of questions
+    Standard,
+    // Batches of questions until every life is lost
+    Survival,
+    // The same questions for everyone playing that day
+    Daily,
+    // Several players taking turns to wager on each question
+    HotSeat,
+    // A computer opponent wagering on the same questions
+    Versus,
+}
+
+// Categories offered by the current Source
+#[derive(Default)]
+struct Categories(Vec<Category>);
+
+// Preset game lengths, between which any other length may be chosen
+const QUESTION_PRESETS: [usize; 4] = [5, 8, 10, 20];
+// OpenTDB serves at most 50 questions per request
+const MAX_QUESTIONS: usize = 50;
+const TOKEN_PRESETS: [usize; 4] = [3, 5, 7, 10];
+const MAX_TOKENS: usize = 10;
+// Seconds allowed per question in timed mode, or None for no time pressure
+const TIME_LIMITS: [Option<u32>; 5] = [None, Some(10), Some(20), Some(30), Some(60)];
+// Players sharing the screen in a hot seat game
+const PLAYER_COUNTS: [usize; 5] = [2, 3, 4, 5, 6];
+// How often the bot's favourite answer is right, as a percentage
+const BOT_ACCURACIES: [Option<u32>; 5] = [None, Some(25), Some(50), Some(75), Some(95)];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Category,
+    Difficulty,
+    Questions,
+    Tokens,
+    TrueFalse,
+    Scoring,
+    TimeLimit,
+    TimeBonus,
+    Mode,
+    Players,
+    Bot,
+    BotSkill,
+}
+
+#[derive(Component)]
+struct SetupElem;
+#[derive(Component)]
+struct SettingValue(Setting);
+#[derive(Component, Clone, Copy)]
+enum SetupButton {
+    Cycle(Setting, i32),
+    Adjust(Setting, i32),
+    Start,
+    Back,
+}
+
+impl Default for GameSettings {
+    fn default() -> GameSettings {
+        GameSettings {
+            category: None,
+            difficulty: None,
+            question_count: 8,
+            token_count: 5,
+            include_true_false: false,
+            scoring: &Linear,
+            time_limit: None,
+            time_bonus: false,
+            mode: GameMode::Standard,
+            players: 2,
+            bot: BotProfile::Calibrated,
+            bot_accuracy: None,
+        }
+    }
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [GameMode::Standard, GameMode::Survival,
+                                    GameMode::Daily, GameMode::HotSeat,
+                                    GameMode::Versus];
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameMode::Standard => write!(f, "Standard"),
+            GameMode::Survival => write!(f, "Survival"),
+            GameMode::Daily => write!(f, "Daily Challenge"),
+            GameMode::HotSeat => write!(f, "Hot Seat"),
+            GameMode::Versus => write!(f, "Versus Bot"),
+        }
+    }
+}
+
+impl Setting {
+    // Whether the Setting is a number which can be adjusted a step at a time
+    fn is_numeric(&self) -> bool {
+        matches!(self, Setting::Questions | Setting::Tokens)
+    }
+}
+
+impl Plugin for SetupPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameSettings::default())
+           .insert_resource(Categories::default())
+           .add_system_set(
+               SystemSet::on_enter(AppState::Setup).with_system(spawn_setup_menu)
+                                                   .with_system(spawn_category_task))
+           .add_system_set(
+               SystemSet::on_update(AppState::Setup).with_system(insert_categories)
+                                                    .with_system(setup_buttons)
+                                                    .with_system(update_values))
+           .add_system_set(
+               SystemSet::on_exit(AppState::Setup).with_system(teardown_setup));
+    }
+}
+
+// Spawns an Async call to retrieve the categories the current Source offers
+fn spawn_category_task(thread_pool: Res<AsyncComputeTaskPool>,
+                       source: Res<Source>,
+                       mut categories: ResMut<Categories>,
+                       mut cmds: Commands,
+) {
+    categories.0.clear();
+
+    let fetch = source.0.categories();
+    let category_get = thread_pool.spawn(async move {
+        Compat::new(fetch).await
+    });
+
+    cmds.spawn().insert(category_get).insert(SetupElem);
+}
+
+// Awaits the category list, dropping the chosen category if it is no longer offered
+#[allow(clippy::type_complexity)]
+fn insert_categories(mut category_task: Query<(Entity, 
+                         &mut Task<Result<Vec<Category>, LoadError>>)>,
+                     mut categories: ResMut<Categories>,
+                     mut settings: ResMut<GameSettings>,
+                     mut cmds: Commands,
+) {
+    for (entity, mut task) in category_task.iter_mut() {
+        if let Some(category_res) = future::block_on(future::poll_once(&mut *task)) {
+            // On failure only "Any Category" is offered
+            categories.0 = category_res.unwrap_or_default();
+
+            if let Some(chosen) = &settings.category {
+                if !categories.0.contains(chosen) {
+                    settings.category = None;
+                }
+            }
+
+            cmds.entity(entity).despawn();
+        }
+    }
+}
+
+// Spawns a row of options for every Setting, along with start and back buttons
+fn spawn_setup_menu(asset_server: Res<AssetServer>, mut cmds: Commands) {
+    let font = asset_server.load("fonts/PublicSans-Medium.ttf");
+
+    cmds.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::ColumnReverse,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        // Title Text
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: Rect::all(Val::Px(10.)),
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "Game Setup",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 50.,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+
+        for (setting, label) in [(Setting::Mode, "Mode"),
+                                 (Setting::Players, "Players"),
+                                 (Setting::Bot, "Bot"),
+                                 (Setting::BotSkill, "Bot Skill"),
+                                 (Setting::Category, "Category"),
+                                 (Setting::Difficulty, "Difficulty"),
+                                 (Setting::Questions, "Questions"),
+                                 (Setting::Tokens, "Tokens"),
+                                 (Setting::TrueFalse, "True/False"),
+                                 (Setting::Scoring, "Scoring"),
+                                 (Setting::TimeLimit, "Time Limit"),
+                                 (Setting::TimeBonus, "Time Bonus")] {
+            spawn_setting_row(parent, font.clone(), setting, label);
+        }
+
+        // Start and Back Buttons
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Px(70.)),
+                justify_content: JustifyContent::SpaceEvenly,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            for (text, button) in [("Back", SetupButton::Back),
+                                   ("Start!", SetupButton::Start)] {
+                parent.spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(150.), Val::Px(50.)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            text,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 40.,
+                                color: Color::rgb(1., 1., 1.),
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                })
+                .insert(button);
+            }
+        });
+    }).insert(SetupElem);
+}
+
+// Spawns a row holding a Setting's label, its current value, and buttons to change
+// it. Numeric Settings get an extra pair of buttons for fine adjustment
+fn spawn_setting_row(parent: &mut ChildBuilder,
+                     font: Handle<Font>,
+                     setting: Setting,
+                     label: &str,
+) {
+    parent.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Px(36.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        // Setting Label
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(150.), Val::Percent(100.)),
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 26.,
+                        color: Color::BLACK,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        });
+
+        let fine = setting.is_numeric();
+        spawn_cycle_button(parent, font.clone(), "<", SetupButton::Cycle(setting, -1));
+        if fine {
+            spawn_cycle_button(parent, font.clone(), "-", 
+                               SetupButton::Adjust(setting, -1));
+        }
+
+        // Setting Value, narrowed to make room for any fine adjustment buttons
+        let value_width = if fine { 332. } else { 420. };
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(value_width), Val::Percent(100.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 24.,
+                        color: Color::BLACK,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            }).insert(SettingValue(setting));
+        });
+
+        if fine {
+            spawn_cycle_button(parent, font.clone(), "+", 
+                               SetupButton::Adjust(setting, 1));
+        }
+        spawn_cycle_button(parent, font, ">", SetupButton::Cycle(setting, 1));
+    });
+}
+
+// Spawns a small arrow button which steps a Setting through its options
+fn spawn_cycle_button(parent: &mut ChildBuilder,
+                      font: Handle<Font>,
+                      arrow: &str,
+                      button: SetupButton,
+) {
+    parent.spawn_bundle(ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(36.), Val::Px(36.)),
+            margin: Rect {
+                left: Val::Px(2.),
+                right: Val::Px(2.),
+                ..Default::default()
+            },
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            text: Text::with_section(
+                arrow,
+                TextStyle {
+                    font,
+                    font_size: 26.,
+                    color: Color::rgb(1., 1., 1.),
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+    })
+    .insert(button);
+}
+
+// Click handler for every button on the setup screen
+fn setup_buttons(mut state: ResMut<State<AppState>>,
+                 mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor, &SetupButton),
+                                  Pressed>,
+                 mut settings: ResMut<GameSettings>,
+                 categories: Res<Categories>,
+                 button_colors: Res<ButtonMaterials>,
+                 mut cmds: Commands,
+) {
+    for (interaction, pad_click, mut color, button) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                match *button {
+                    SetupButton::Cycle(setting, step) => {
+                        cycle_setting(&mut settings, &categories, setting, step);
+                    },
+                    SetupButton::Adjust(setting, step) => {
+                        adjust_setting(&mut settings, setting, step);
+                    },
+                    SetupButton::Start => {
+                        if settings.mode == GameMode::Daily {
+                            let daily = DailySource::today();
+                            cmds.insert_resource(daily.date);
+                            cmds.insert_resource(Source(Arc::new(daily)));
+                        }
+                        state.set(AppState::Load).unwrap();
+                    },
+                    SetupButton::Back => state.set(AppState::Menu).unwrap(),
+                }
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            }
+        }
+    }
+}
+
+// Steps a Setting forwards or backwards through its options, wrapping at either end
+fn cycle_setting(settings: &mut GameSettings,
+                 categories: &Categories,
+                 setting: Setting,
+                 step: i32,
+) {
+    match setting {
+        Setting::Category => {
+            let options: Vec<Option<Category>> = std::iter::once(None)
+                .chain(categories.0.iter().cloned().map(Some))
+                .collect();
+            settings.category = cycle(&options, &settings.category, step);
+        },
+        Setting::Difficulty => {
+            let options: Vec<Option<Difficulty>> = std::iter::once(None)
+                .chain(Difficulty::ALL.into_iter().map(Some))
+                .collect();
+            settings.difficulty = cycle(&options, &settings.difficulty, step);
+        },
+        Setting::Questions => {
+            settings.question_count = cycle_preset(&QUESTION_PRESETS, 
+                                                   settings.question_count, step);
+        },
+        Setting::Tokens => {
+            settings.token_count = cycle_preset(&TOKEN_PRESETS, 
+                                                settings.token_count, step);
+        },
+        Setting::TrueFalse => {
+            settings.include_true_false = !settings.include_true_false;
+        },
+        Setting::Scoring => {
+            settings.scoring = cycle(&SCORING_RULES, &settings.scoring, step);
+        },
+        Setting::TimeLimit => {
+            settings.time_limit = cycle(&TIME_LIMITS, &settings.time_limit, step);
+        },
+        Setting::TimeBonus => {
+            settings.time_bonus = !settings.time_bonus;
+        },
+        Setting::Mode => {
+            settings.mode = cycle(&GameMode::ALL, &settings.mode, step);
+        },
+        Setting::Players => {
+            settings.players = cycle(&PLAYER_COUNTS, &settings.players, step);
+        },
+        Setting::Bot => {
+            settings.bot = cycle(&BotProfile::ALL, &settings.bot, step);
+        },
+        Setting::BotSkill => {
+            settings.bot_accuracy = cycle(&BOT_ACCURACIES, &settings.bot_accuracy, step);
+        },
+    }
+}
+
+// Nudges a numeric Setting up or down by step, within its limits
+fn adjust_setting(settings: &mut GameSettings, setting: Setting, step: i32) {
+    match setting {
+        Setting::Questions => {
+            settings.question_count = (settings.question_count as i32 + step)
+                .clamp(1, MAX_QUESTIONS as i32) as usize;
+        },
+        Setting::Tokens => {
+            settings.token_count = (settings.token_count as i32 + step)
+                .clamp(1, MAX_TOKENS as i32) as usize;
+        },
+        _ => (),
+    }
+}
+
+// Returns the option step places away from current
+pub fn cycle<T: Clone + PartialEq>(options: &[T], current: &T, step: i32) -> T {
+    let len = options.len() as i32;
+    let index = options.iter().position(|option| option == current).unwrap_or(0) as i32;
+
+    options[(index + step).rem_euclid(len) as usize].clone()
+}
+
+// Returns the next preset past current in the direction of step, wrapping at either
+// end. Works even when current lies between presets
+fn cycle_preset(presets: &[usize], current: usize, step: i32) -> usize {
+    if step > 0 {
+        presets.iter().copied().find(|&preset| preset > current).unwrap_or(presets[0])
+    } else {
+        presets.iter().rev().copied().find(|&preset| preset < current)
+               .unwrap_or(presets[presets.len() - 1])
+    }
+}
+
+// Displays a numeric Setting, noting when it is not one of the presets
+fn preset_label(presets: &[usize], value: usize) -> String {
+    if presets.contains(&value) {
+        value.to_string()
+    } else {
+        format!("{} (Custom)", value)
+    }
+}
+
+// Keeps the displayed value of every Setting in sync with GameSettings
+fn update_values(mut value_query: Query<(&mut Text, &SettingValue, 
+                                         ChangeTrackers<SettingValue>)>,
+                 settings: Res<GameSettings>,
+) {
+    for (mut text, value, tracker) in value_query.iter_mut() {
+        if settings.is_changed() || tracker.is_added() {
+            text.sections[0].value = match value.0 {
+                Setting::Category => match &settings.category {
+                    Some(category) => category.name.clone(),
+                    None => String::from("Any Category"),
+                },
+                Setting::Difficulty => match settings.difficulty {
+                    Some(difficulty) => difficulty.to_string(),
+                    None => String::from("Any Difficulty"),
+                },
+                Setting::Questions => {
+                    preset_label(&QUESTION_PRESETS, settings.question_count)
+                },
+                Setting::Tokens => {
+                    preset_label(&TOKEN_PRESETS, settings.token_count)
+                },
+                Setting::TrueFalse => if settings.include_true_false {
+                    String::from("Included")
+                } else {
+                    String::from("Excluded")
+                },
+                Setting::Scoring => String::from(settings.scoring.name()),
+                Setting::TimeLimit => match settings.time_limit {
+                    Some(seconds) => format!("{} Seconds", seconds),
+                    None => String::from("Off"),
+                },
+                Setting::TimeBonus => if settings.time_bonus {
+                    String::from("On")
+                } else {
+                    String::from("Off")
+                },
+                Setting::Mode => settings.mode.to_string(),
+                Setting::Players => format!("{} Players", settings.players),
+                Setting::Bot => settings.bot.to_string(),
+                Setting::BotSkill => match settings.bot_accuracy {
+                    Some(percent) => format!("Right {}% of the Time", percent),
+                    None => String::from("Varies by Difficulty"),
+                },
+            };
+        }
+    }
+}
+
+// Tears down the setup screen and any outstanding category request
+fn teardown_setup(setup_query: Query<Entity, With<SetupElem>>, mut cmds: Commands) {
+    for setup_id in setup_query.iter() {
+        cmds.entity(setup_id).despawn_recursive();
+    }
+}
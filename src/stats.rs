@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use crate::{AppState, ButtonMaterials};
+use crate::gamepad::{self, PadClick, Pressed};
+use crate::game::source::Difficulty;
+use crate::storage::{read_json, write_json};
+
+const HISTORY_FILE: &str = "game_history.json";
+// Fewest questions a category needs before it can be named best or worst
+const MIN_CATEGORY_QUESTIONS: usize = 3;
+
+pub struct StatsPlugin;
+
+// A completed game, kept question by question so it can be broken down later
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub played_at: u64,
+    pub score: i32,
+    pub max_score: i32,
+    pub questions: Vec<QuestionRecord>,
+}
+
+// How many of the player's tokens landed on the correct answer to a question
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestionRecord {
+    pub category: String,
+    pub difficulty: String,
+    pub token_count: usize,
+    pub tokens_correct: usize,
+}
+
+// Running totals of tokens wagered and tokens placed correctly
+#[derive(Default, Clone, Copy)]
+struct Tally {
+    questions: usize,
+    tokens: usize,
+    correct: usize,
+}
+
+#[derive(Component)]
+struct StatsElem;
+#[derive(Component)]
+struct BackButton;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+               SystemSet::on_enter(AppState::Stats).with_system(spawn_stats))
+           .add_system_set(
+               SystemSet::on_update(AppState::Stats).with_system(back_button))
+           .add_system_set(
+               SystemSet::on_exit(AppState::Stats).with_system(teardown_stats));
+    }
+}
+
+impl Tally {
+    fn add(&mut self, question: &QuestionRecord) {
+        self.questions += 1;
+        self.tokens += question.token_count;
+        self.correct += question.tokens_correct;
+    }
+
+    // Share of tokens wagered which landed on the correct answer
+    fn accuracy(&self) -> f32 {
+        if self.tokens == 0 {
+            0.
+        } else {
+            self.correct as f32 / self.tokens as f32
+        }
+    }
+}
+
+// Adds a completed game to the history file
+pub fn record(game: GameRecord) {
+    let mut history: Vec<GameRecord> = read_json(HISTORY_FILE);
+    history.push(game);
+    write_json(HISTORY_FILE, &history);
+}
+
+// Spawns a summary of every game in the history file, beside a breakdown of
+// accuracy by category
+fn spawn_stats(asset_server: Res<AssetServer>,
+               button_colors: Res<ButtonMaterials>,
+               mut cmds: Commands,
+) {
+    let font = asset_server.load("fonts/PublicSans-Medium.ttf");
+    let history: Vec<GameRecord> = read_json(HISTORY_FILE);
+
+    let mut overall = Tally::default();
+    let mut by_category: HashMap<String, Tally> = HashMap::new();
+    let mut by_difficulty: HashMap<String, Tally> = HashMap::new();
+    for question in history.iter().flat_map(|game| game.questions.iter()) {
+        overall.add(question);
+        by_category.entry(question.category.clone()).or_default().add(question);
+        by_difficulty.entry(difficulty_name(&question.difficulty))
+                     .or_default()
+                     .add(question);
+    }
+
+    let mut categories: Vec<(String, Tally)> = by_category.into_iter().collect();
+    categories.sort_by(|a, b| b.1.accuracy().total_cmp(&a.1.accuracy())
+                                            .then_with(|| a.0.cmp(&b.0)));
+
+    let summary = if history.is_empty() {
+        String::from("No games played yet!")
+    } else {
+        summary_text(&history, &overall, &categories, &by_difficulty)
+    };
+    let category_lines: String = categories.iter()
+        .map(|(category, tally)| {
+            format!("{}: {:.0}% ({} questions)\n", category, 100. * tally.accuracy(),
+                    tally.questions)
+        })
+        .collect();
+
+    cmds.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::ColumnReverse,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        // Title Text
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: Rect::all(Val::Px(15.)),
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "Lifetime Statistics",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 50.,
+                    color: Color::BLACK,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        });
+
+        // Stats Columns
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(95.), Val::Percent(72.)),
+                justify_content: JustifyContent::SpaceBetween,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            for (text, font_size) in [(summary, 22.), (category_lines, 16.)] {
+                parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(50.), Val::Percent(100.)),
+                        flex_direction: FlexDirection::ColumnReverse,
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                }).with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        style: Style {
+                            max_size: Size::new(Val::Px(370.), Val::Undefined),
+                            ..Default::default()
+                        },
+                        text: Text::with_section(
+                            text,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size,
+                                color: Color::BLACK,
+                            },
+                            Default::default(),
+                        ),
+                        ..Default::default()
+                    });
+                });
+            }
+        });
+
+        // Back Button
+        parent.spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(150.), Val::Px(50.)),
+                margin: Rect::all(Val::Px(10.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: button_colors.none,
+            ..Default::default()
+        }).with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "Back",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+        }).insert(BackButton);
+    }).insert(StatsElem);
+}
+
+// Games played, average score, and accuracy overall, by difficulty, and in the
+// best and worst categories
+fn summary_text(history: &[GameRecord],
+                overall: &Tally,
+                categories: &[(String, Tally)],
+                by_difficulty: &HashMap<String, Tally>,
+) -> String {
+    let games = history.len() as f32;
+    let average_score = history.iter().map(|game| game.score).sum::<i32>() as f32 / games;
+    let average_max = history.iter().map(|game| game.max_score).sum::<i32>() as f32 / games;
+    let average_correct = overall.correct as f32 / overall.questions.max(1) as f32;
+    let average_tokens = overall.tokens as f32 / overall.questions.max(1) as f32;
+
+    let mut text = format!("Games Played: {}\n\
+                            Average Score: {:.1} of {:.1}\n\
+                            Average Tokens on the Correct Answer: {:.1} of {:.1}\n\
+                            Overall Accuracy: {:.0}%\n\n",
+                           history.len(), average_score, average_max, average_correct,
+                           average_tokens, 100. * overall.accuracy());
+
+    for difficulty in Difficulty::ALL {
+        if let Some(tally) = by_difficulty.get(&difficulty.to_string()) {
+            text += &format!("{} Accuracy: {:.0}%\n", difficulty, 100. * tally.accuracy());
+        }
+    }
+
+    let mut ranked = categories.iter()
+        .filter(|(_, tally)| tally.questions >= MIN_CATEGORY_QUESTIONS);
+    if let Some((best, tally)) = ranked.next() {
+        text += &format!("\nBest Category: {} ({:.0}%)", best, 100. * tally.accuracy());
+    }
+    if let Some((worst, tally)) = ranked.next_back() {
+        text += &format!("\nWorst Category: {} ({:.0}%)", worst, 100. * tally.accuracy());
+    }
+
+    text
+}
+
+// Capitalizes a difficulty as it is displayed elsewhere, leaving unknown ones be
+fn difficulty_name(difficulty: &str) -> String {
+    Difficulty::ALL.into_iter()
+        .find(|known| known.as_str().eq_ignore_ascii_case(difficulty))
+        .map(|known| known.to_string())
+        .unwrap_or_else(|| String::from(difficulty))
+}
+
+// Click handler for returning to the menu
+#[allow(clippy::type_complexity)]
+fn back_button(mut query: Query<(&Interaction, Option<&PadClick>, &mut UiColor),
+                                (Pressed, With<BackButton>)>,
+               button_colors: Res<ButtonMaterials>,
+               mut state: ResMut<State<AppState>>,
+) {
+    for (interaction, pad_click, mut color) in query.iter_mut() {
+        match gamepad::interaction(interaction, pad_click) {
+            Interaction::Clicked => {
+                *color = button_colors.clicked;
+                state.set(AppState::Menu).unwrap();
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered;
+            },
+            Interaction::None => {
+                *color = button_colors.none;
+            },
+        }
+    }
+}
+
+// Despawns the stats screen
+fn teardown_stats(stats_query: Query<Entity, With<StatsElem>>, mut cmds: Commands) {
+    for stats_id in stats_query.iter() {
+        cmds.entity(stats_id).despawn_recursive();
+    }
+}
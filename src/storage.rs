@@ -0,0 +1,109 @@
+use bevy::log::warn;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Root of the assets folder, resolved the same way Bevy's AssetServer resolves it
+pub fn assets_dir() -> PathBuf {
+    let root = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        PathBuf::from(manifest_dir)
+    } else {
+        env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(|parent| parent.to_path_buf()))
+            .unwrap_or_default()
+    };
+
+    root.join("assets")
+}
+
+// Per-user directory where PrettySure keeps its own files, if the platform has one
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("PrettySure"))
+}
+
+// Reads a JSON file from the data directory, falling back to the default if it is
+// missing or malformed
+pub fn read_json<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    data_dir()
+        .and_then(|dir| fs::read_to_string(dir.join(file_name)).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// Writes a JSON file to the data directory, creating the directory if need be
+pub fn write_json<T: Serialize>(file_name: &str, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(contents) => {
+            write_text(file_name, &contents);
+        },
+        Err(e) => warn!("Couldn't save {}: {}", file_name, e),
+    }
+}
+
+// Writes a text file to the data directory, creating the directory if need be, and
+// returns where it was written. The file is written whole to a temporary file first,
+// so a reader never finds it half written
+pub fn write_text(file_name: &str, contents: &str) -> Option<PathBuf> {
+    let dir = data_dir()?;
+    let path = dir.join(file_name);
+    let temp_path = dir.join(format!("{}.tmp", file_name));
+
+    let written = fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&temp_path, contents))
+        .and_then(|_| fs::rename(&temp_path, &path));
+    match written {
+        Ok(()) => Some(path),
+        Err(e) => {
+            warn!("Couldn't save {}: {}", file_name, e);
+            None
+        },
+    }
+}
+
+// Seconds since the Unix epoch
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+// Calendar date (year, month, day) in UTC of a time given in seconds since the Unix
+// epoch, using Howard Hinnant's days-to-civil algorithm
+pub fn civil_date(unix_time: u64) -> (i64, u32, u32) {
+    let days = (unix_time / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
+                       - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 
+                                    - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month as u32, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(86_399), (1970, 1, 1));
+        assert_eq!(civil_date(86_400), (1970, 1, 2));
+        // Leap days, including that of a century divisible by 400
+        assert_eq!(civil_date(951_782_400), (2000, 2, 29));
+        assert_eq!(civil_date(951_868_800), (2000, 3, 1));
+        assert_eq!(civil_date(1_709_164_800), (2024, 2, 29));
+        assert_eq!(civil_date(1_735_689_599), (2024, 12, 31));
+        assert_eq!(civil_date(1_735_689_600), (2025, 1, 1));
+    }
+}